@@ -0,0 +1,322 @@
+use crate::{
+    client::decode_jws,
+    error::{Error, Expiry, Mismatch, Missing, Validation},
+    Claims, Client, Confirmation, Discovered,
+};
+use biscuit::{
+    jwa::SignatureAlgorithm, jwk::JWKSet, jws::Compact, CompactJson, Empty, SingleOrMultiple,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use url::Url;
+
+/// Claims of a JWT access token.
+///
+/// See [RFC 9068, section 2.2](https://tools.ietf.org/html/rfc9068#section-2.2). Other claims
+/// the API relies on are deserialized into `extra`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AccessTokenClaims<T = Empty> {
+    pub iss: Url,
+    pub exp: i64,
+    pub aud: SingleOrMultiple<String>,
+    pub sub: String,
+    pub client_id: String,
+    pub iat: i64,
+    pub jti: String,
+    #[serde(default)]
+    pub scope: Option<String>,
+    #[serde(default)]
+    pub auth_time: Option<i64>,
+    #[serde(default)]
+    pub acr: Option<String>,
+    #[serde(default)]
+    pub amr: Option<Vec<String>>,
+    #[serde(default)]
+    pub cnf: Option<Confirmation>,
+    #[serde(flatten)]
+    pub extra: T,
+}
+
+impl<T: Serialize + DeserializeOwned> CompactJson for AccessTokenClaims<T> {}
+
+impl<T> AccessTokenClaims<T> {
+    /// The space separated scopes of the token.
+    pub fn scopes(&self) -> impl Iterator<Item = &str> {
+        self.scope.as_deref().unwrap_or_default().split_whitespace()
+    }
+}
+
+/// Validates JWT access tokens presented to a resource server, with the signing keys of the
+/// issuer.
+///
+/// See [RFC 9068, section 4](https://tools.ietf.org/html/rfc9068#section-4).
+#[derive(Debug)]
+pub struct AccessTokenValidator {
+    /// Issuer the tokens must come from.
+    pub issuer: Url,
+    /// Identifiers of this resource server, the token audience must contain one of them.
+    pub audiences: Vec<String>,
+    /// Signing keys of the issuer.
+    pub jwks: JWKSet<Empty>,
+    /// Signature algorithms the tokens may use. Empty allows the asymmetric ones.
+    pub algorithms: Vec<SignatureAlgorithm>,
+    /// Scopes the token must all have.
+    pub required_scopes: Vec<String>,
+    /// Clients the token may be issued to. Any client if empty.
+    pub client_ids: Vec<String>,
+    /// Allowed clock skew when checking `exp` and `iat`.
+    pub leeway: Duration,
+}
+
+impl AccessTokenValidator {
+    pub fn new(issuer: Url, audience: impl Into<String>, jwks: JWKSet<Empty>) -> Self {
+        Self {
+            issuer,
+            audiences: vec![audience.into()],
+            jwks,
+            algorithms: Vec::new(),
+            required_scopes: Vec::new(),
+            client_ids: Vec::new(),
+            leeway: Duration::seconds(60),
+        }
+    }
+
+    /// A validator for tokens of the provider a discovered client uses, with its current keys.
+    pub fn from_client<C: CompactJson + Claims>(
+        client: &Client<Discovered, C>,
+        audience: impl Into<String>,
+    ) -> Self {
+        let keys = client.jwks.as_ref().map(|jwks| jwks.keys.clone());
+        let jwks = JWKSet {
+            keys: keys.unwrap_or_default(),
+        };
+        Self::new(client.config().issuer.clone(), audience, jwks)
+    }
+
+    /// Decodes and validates an access token, returning its claims. Errors are:
+    ///
+    /// - Validation::Mismatch::TokenType if the `typ` header is not `at+jwt`
+    /// - Errors of `Client::decode_token` if the signature cannot be verified
+    /// - Error::Jose if a required claim is missing
+    /// - Validation::Mismatch::Issuer if the token is from another issuer
+    /// - Validation::Missing::Audience if the token is not for one of the audiences
+    /// - Validation::Mismatch::ClientId if the token was issued to a client that is not allowed
+    /// - Validation::Missing::Scope if a required scope is missing
+    /// - Validation::Expired::Expires if the token has expired
+    /// - Validation::Expired::NotYetValid if the token was issued in the future
+    pub fn validate<T: Serialize + DeserializeOwned>(
+        &self,
+        token: &str,
+    ) -> Result<AccessTokenClaims<T>, Error> {
+        let mut token: Compact<AccessTokenClaims<T>, Empty> = Compact::new_encoded(token);
+
+        let header = token.unverified_header()?;
+        let typ = header.registered.media_type.unwrap_or_default();
+        let typ_ok =
+            typ.eq_ignore_ascii_case("at+jwt") || typ.eq_ignore_ascii_case("application/at+jwt");
+        if !typ_ok {
+            let expected = String::from("at+jwt");
+            return Err(Validation::Mismatch(Mismatch::TokenType {
+                expected,
+                actual: typ,
+            })
+            .into());
+        }
+
+        let algorithms = Some(self.algorithms.as_slice()).filter(|algs| !algs.is_empty());
+        decode_jws(&self.jwks, &mut token, algorithms)?;
+        let claims = token.unwrap_decoded().1;
+
+        if claims.iss != self.issuer {
+            let expected = self.issuer.as_str().to_string();
+            let actual = claims.iss.as_str().to_string();
+            return Err(Validation::Mismatch(Mismatch::Issuer { expected, actual }).into());
+        }
+
+        let for_us = self
+            .audiences
+            .iter()
+            .any(|audience| claims.aud.contains(audience));
+        if !for_us {
+            return Err(Validation::Missing(Missing::Audience).into());
+        }
+
+        if !self.client_ids.is_empty() && !self.client_ids.contains(&claims.client_id) {
+            let expected = self.client_ids.join(" ");
+            let actual = claims.client_id;
+            return Err(Validation::Mismatch(Mismatch::ClientId { expected, actual }).into());
+        }
+
+        for scope in &self.required_scopes {
+            if !claims.scopes().any(|granted| granted == scope) {
+                return Err(Validation::Missing(Missing::Scope(scope.clone())).into());
+            }
+        }
+
+        let now = Utc::now();
+        if claims.exp <= (now - self.leeway).timestamp() {
+            return Err(Validation::Expired(Expiry::Expires(timestamp(claims.exp))).into());
+        }
+        if claims.iat > (now + self.leeway).timestamp() {
+            return Err(Validation::Expired(Expiry::NotYetValid(timestamp(claims.iat))).into());
+        }
+
+        Ok(claims)
+    }
+}
+
+fn timestamp(seconds: i64) -> chrono::naive::NaiveDateTime {
+    DateTime::from_timestamp(seconds, 0)
+        .unwrap_or_default()
+        .naive_utc()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccessTokenClaims, AccessTokenValidator};
+    use crate::error::{Decode, Error, Mismatch, Missing, Validation};
+    use biscuit::{
+        jwa::SignatureAlgorithm,
+        jwk::JWKSet,
+        jws::{Compact, RegisteredHeader, Secret},
+        Empty, SingleOrMultiple,
+    };
+    use chrono::Utc;
+    use ring::{
+        rand::SystemRandom,
+        signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING},
+    };
+    use serde::{Deserialize, Serialize};
+    use std::sync::Arc;
+    use url::Url;
+
+    #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+    struct Tenant {
+        tenant: String,
+    }
+
+    fn validator() -> AccessTokenValidator {
+        let jwks: JWKSet<Empty> = serde_json::from_str(
+            r#"{ "keys": [{ "kty": "oct", "kid": "key", "k": "c2VjcmV0" }] }"#,
+        )
+        .unwrap();
+        let issuer = Url::parse("https://example.com").unwrap();
+        let mut validator = AccessTokenValidator::new(issuer, "https://api.example.com", jwks);
+        validator.algorithms = vec![SignatureAlgorithm::HS256];
+        validator
+    }
+
+    fn token(typ: &str, scope: &str) -> String {
+        let secret = Secret::Bytes(b"secret".to_vec());
+        signed(SignatureAlgorithm::HS256, "key", &secret, typ, scope)
+    }
+
+    fn signed(
+        algorithm: SignatureAlgorithm,
+        kid: &str,
+        secret: &Secret,
+        typ: &str,
+        scope: &str,
+    ) -> String {
+        let header = RegisteredHeader {
+            algorithm,
+            media_type: Some(typ.to_string()),
+            key_id: Some(kid.to_string()),
+            ..Default::default()
+        };
+        let now = Utc::now().timestamp();
+        let claims = AccessTokenClaims {
+            iss: Url::parse("https://example.com").unwrap(),
+            exp: now + 300,
+            aud: SingleOrMultiple::Single(String::from("https://api.example.com")),
+            sub: String::from("user"),
+            client_id: String::from("client"),
+            iat: now,
+            jti: String::from("id"),
+            scope: Some(scope.to_string()),
+            auth_time: None,
+            acr: None,
+            amr: None,
+            cnf: None,
+            extra: Tenant {
+                tenant: String::from("acme"),
+            },
+        };
+        Compact::new_decoded(From::from(header), claims)
+            .encode(secret)
+            .unwrap()
+            .encoded()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn valid() {
+        let mut validator = validator();
+        validator.required_scopes = vec![String::from("read")];
+        validator.client_ids = vec![String::from("client")];
+        let claims: AccessTokenClaims<Tenant> =
+            validator.validate(&token("at+jwt", "read write")).unwrap();
+        assert_eq!("user", claims.sub);
+        assert_eq!("acme", claims.extra.tenant);
+    }
+
+    #[test]
+    fn rejects_id_tokens() {
+        match validator().validate::<Empty>(&token("JWT", "read")) {
+            Err(Error::Validation(Validation::Mismatch(Mismatch::TokenType { .. }))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn required_scope() {
+        let mut validator = validator();
+        validator.required_scopes = vec![String::from("admin")];
+        match validator.validate::<Empty>(&token("application/at+jwt", "read")) {
+            Err(Error::Validation(Validation::Missing(Missing::Scope(scope)))) => {
+                assert_eq!("admin", scope)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn audience() {
+        let mut validator = validator();
+        validator.audiences = vec![String::from("https://other.example.com")];
+        match validator.validate::<Empty>(&token("at+jwt", "read")) {
+            Err(Error::Validation(Validation::Missing(Missing::Audience))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ec_keys() {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+        let key =
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref()).unwrap();
+        let point = key.public_key().as_ref();
+        let b64 = |bytes: &[u8]| base64::encode_config(bytes, base64::URL_SAFE_NO_PAD);
+        let mut validator = validator();
+        validator.algorithms = Vec::new();
+        validator.jwks = serde_json::from_value(serde_json::json!({ "keys": [
+            { "kty": "EC", "kid": "p256", "crv": "P-256", "x": b64(&point[1..33]), "y": b64(&point[33..]) },
+            { "kty": "EC", "kid": "p521", "crv": "P-521", "x": "AA", "y": "AA" }
+        ] }))
+        .unwrap();
+        let secret = Secret::EcdsaKeyPair(Arc::new(key));
+
+        let token = signed(SignatureAlgorithm::ES256, "p256", &secret, "at+jwt", "read");
+        let claims = validator.validate::<Empty>(&token).unwrap();
+        assert_eq!("user", claims.sub);
+
+        // A token picking a key that cannot be verified is rejected, not a panic
+        let token = signed(SignatureAlgorithm::ES256, "p521", &secret, "at+jwt", "read");
+        match validator.validate::<Empty>(&token) {
+            Err(Error::Decode(Decode::UnsupportedKey(curve))) => assert_eq!("P-521", curve),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
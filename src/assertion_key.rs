@@ -0,0 +1,122 @@
+use crate::error::Jose;
+use biscuit::{
+    jwa::SignatureAlgorithm,
+    jws::{Compact, Header, RegisteredHeader, Secret},
+    CompactJson, Empty,
+};
+use chrono::{Duration, Utc};
+use ring::{
+    rand::{SecureRandom, SystemRandom},
+    signature::{EcdsaKeyPair, RsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING},
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc};
+
+#[derive(Clone)]
+enum Key {
+    Rsa(Arc<RsaKeyPair>),
+    Ecdsa(Arc<EcdsaKeyPair>),
+    Hmac(Vec<u8>),
+}
+
+/// Claims of an assertion, see [RFC 7523, section 3](https://tools.ietf.org/html/rfc7523#section-3).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub(crate) struct AssertionClaims {
+    pub iss: String,
+    pub sub: String,
+    pub aud: String,
+    pub exp: i64,
+    pub iat: i64,
+    pub jti: String,
+}
+
+impl CompactJson for AssertionClaims {}
+
+/// A key signing JWT assertions, such as the private key of a service account.
+///
+/// See [RFC 7523](https://tools.ietf.org/html/rfc7523).
+#[derive(Clone)]
+pub struct AssertionKey {
+    /// The `iss` of the assertions, e.g. the service account or client id.
+    pub issuer: String,
+    /// The `kid` header of the assertions, for providers that keep several keys per issuer.
+    pub key_id: Option<String>,
+    /// How long assertions are valid, 5 minutes by default.
+    pub lifetime: Duration,
+    key: Key,
+}
+
+impl AssertionKey {
+    /// An RS256 key from a PKCS#8 or PKCS#1 DER encoded RSA private key.
+    pub fn rsa(issuer: impl Into<String>, der: &[u8]) -> Result<Self, Jose> {
+        let key_pair = match RsaKeyPair::from_pkcs8(der) {
+            Ok(key_pair) => key_pair,
+            Err(_) => RsaKeyPair::from_der(der)?,
+        };
+        Ok(Self::with_key(issuer, Key::Rsa(Arc::new(key_pair))))
+    }
+
+    /// An ES256 key from a PKCS#8 DER encoded P-256 private key.
+    pub fn ecdsa(issuer: impl Into<String>, pkcs8: &[u8]) -> Result<Self, Jose> {
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8)?;
+        Ok(Self::with_key(issuer, Key::Ecdsa(Arc::new(key_pair))))
+    }
+
+    /// An HS256 key from a shared secret.
+    pub fn hmac(issuer: impl Into<String>, secret: impl Into<Vec<u8>>) -> Self {
+        Self::with_key(issuer, Key::Hmac(secret.into()))
+    }
+
+    fn with_key(issuer: impl Into<String>, key: Key) -> Self {
+        Self {
+            issuer: issuer.into(),
+            key_id: None,
+            lifetime: Duration::minutes(5),
+            key,
+        }
+    }
+
+    /// Signs an assertion about the subject for the audience, usually the token endpoint.
+    pub fn assertion(&self, subject: &str, audience: &str) -> Result<String, Jose> {
+        let mut jti = [0; 16];
+        SystemRandom::new().fill(&mut jti)?;
+
+        let (algorithm, secret) = match self.key {
+            Key::Rsa(ref key_pair) => (
+                SignatureAlgorithm::RS256,
+                Secret::RsaKeyPair(key_pair.clone()),
+            ),
+            Key::Ecdsa(ref key_pair) => (
+                SignatureAlgorithm::ES256,
+                Secret::EcdsaKeyPair(key_pair.clone()),
+            ),
+            Key::Hmac(ref bytes) => (SignatureAlgorithm::HS256, Secret::Bytes(bytes.clone())),
+        };
+        let header: Header<Empty> = From::from(RegisteredHeader {
+            algorithm,
+            key_id: self.key_id.clone(),
+            ..Default::default()
+        });
+        let now = Utc::now();
+        let claims = AssertionClaims {
+            iss: self.issuer.clone(),
+            sub: subject.to_string(),
+            aud: audience.to_string(),
+            exp: (now + self.lifetime).timestamp(),
+            iat: now.timestamp(),
+            jti: base64::encode_config(jti, base64::URL_SAFE_NO_PAD),
+        };
+        let assertion = Compact::new_decoded(header, claims).encode(&secret)?;
+        Ok(assertion.encoded()?.to_string())
+    }
+}
+
+impl fmt::Debug for AssertionKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AssertionKey")
+            .field("issuer", &self.issuer)
+            .field("key_id", &self.key_id)
+            .field("lifetime", &self.lifetime)
+            .finish()
+    }
+}
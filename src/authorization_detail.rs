@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// One entry of the `authorization_details` of a rich authorization request, describing a
+/// fine-grained permission such as a payment to initiate or an account to read.
+///
+/// See [RFC 9396, section 2](https://tools.ietf.org/html/rfc9396#section-2).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AuthorizationDetail {
+    /// The kind of authorization, as advertised in `authorization_details_types_supported`.
+    #[serde(rename = "type")]
+    pub detail_type: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub datatypes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub privileges: Vec<String>,
+    /// Fields specific to the type, e.g. `instructedAmount` for a payment.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl AuthorizationDetail {
+    pub fn new(detail_type: impl Into<String>) -> Self {
+        Self {
+            detail_type: detail_type.into(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AuthorizationDetail;
+    use serde_json::json;
+
+    #[test]
+    fn round_trip() {
+        let json = json!({
+            "type": "payment_initiation",
+            "actions": ["initiate", "status"],
+            "locations": ["https://example.com/payments"],
+            "instructedAmount": { "currency": "EUR", "amount": "123.50" },
+            "creditorName": "Merchant A"
+        });
+        let detail: AuthorizationDetail = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!("payment_initiation", detail.detail_type);
+        assert_eq!(vec!["initiate", "status"], detail.actions);
+        assert_eq!("Merchant A", detail.extra["creditorName"]);
+        assert_eq!(json, serde_json::to_value(&detail).unwrap());
+    }
+}
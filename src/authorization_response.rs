@@ -0,0 +1,123 @@
+use crate::{
+    error::{ClientError, Error},
+    OAuth2Error, OAuth2ErrorCode,
+};
+use url::{form_urlencoded, Url};
+
+/// The parameters the provider sends back to the redirect URI after the user authorizes.
+///
+/// The same result is produced regardless of the response mode, so a `query` callback and a
+/// `form_post` callback can share the rest of the login flow.
+///
+/// See [spec 3.1.2.5](https://openid.net/specs/openid-connect-core-1_0.html#AuthResponse).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationResponse {
+    /// The authorization code to exchange with `Client::authenticate`.
+    pub code: String,
+    /// The state value from the authorization request, if one was sent.
+    pub state: Option<String>,
+}
+
+impl AuthorizationResponse {
+    /// Parses the query of the redirect URI the provider sent the user back to.
+    pub fn from_url(url: &Url) -> Result<Self, Error> {
+        Self::from_query(url.query().unwrap_or_default())
+    }
+
+    /// Parses the query string of a `response_mode=query` callback.
+    pub fn from_query(query: &str) -> Result<Self, Error> {
+        Self::parse(query.as_bytes())
+    }
+
+    /// Parses the `application/x-www-form-urlencoded` body of a `response_mode=form_post`
+    /// callback.
+    ///
+    /// See [OAuth 2.0 Form Post Response Mode](https://openid.net/specs/oauth-v2-form-post-response-mode-1_0.html).
+    pub fn from_form_post(body: &[u8]) -> Result<Self, Error> {
+        Self::parse(body)
+    }
+
+    /// Errors are:
+    ///
+    /// - Error::ClientError with an OAuth2Error if the provider returned an error response
+    /// - Error::MissingCode if there is neither an error nor a code
+    fn parse(input: &[u8]) -> Result<Self, Error> {
+        let mut code = None;
+        let mut state = None;
+        let mut error = None;
+        let mut error_description = None;
+        let mut error_uri = None;
+
+        for (key, value) in form_urlencoded::parse(input) {
+            match key.as_ref() {
+                "code" => code = Some(value.into_owned()),
+                "state" => state = Some(value.into_owned()),
+                "error" => error = Some(value.into_owned()),
+                "error_description" => error_description = Some(value.into_owned()),
+                "error_uri" => error_uri = Some(value.into_owned()),
+                _ => {}
+            }
+        }
+
+        if let Some(error) = error {
+            let error = OAuth2Error {
+                error: OAuth2ErrorCode::from(error.as_str()),
+                error_description,
+                error_uri,
+            };
+            return Err(ClientError::from(error).into());
+        }
+
+        match code {
+            Some(code) => Ok(Self { code, state }),
+            None => Err(Error::MissingCode),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AuthorizationResponse;
+    use crate::error::{ClientError, Error};
+    use crate::OAuth2ErrorCode;
+    use url::Url;
+
+    #[test]
+    fn query_and_form_post_agree() {
+        let url =
+            Url::parse("https://rp.example.com/cb?code=SplxlOBeZQQYbYS6WxSbIA&state=af0ifjsldkj")
+                .unwrap();
+        let from_query = AuthorizationResponse::from_url(&url).unwrap();
+        let from_post =
+            AuthorizationResponse::from_form_post(b"code=SplxlOBeZQQYbYS6WxSbIA&state=af0ifjsldkj")
+                .unwrap();
+        assert_eq!(from_query, from_post);
+        assert_eq!("SplxlOBeZQQYbYS6WxSbIA", from_post.code);
+        assert_eq!(Some("af0ifjsldkj".to_string()), from_post.state);
+    }
+
+    #[test]
+    fn error_response() {
+        let result = AuthorizationResponse::from_form_post(
+            b"error=invalid_request&error_description=Unsupported%20response_type&state=xyz",
+        );
+        match result {
+            Err(Error::ClientError(ClientError::OAuth2(error))) => {
+                assert_eq!(OAuth2ErrorCode::InvalidRequest, error.error);
+                assert_eq!(
+                    Some("Unsupported response_type".to_string()),
+                    error.error_description
+                );
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn missing_code() {
+        match AuthorizationResponse::from_query("state=xyz") {
+            Err(Error::MissingCode) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,223 @@
+/*!
+Client-Initiated Backchannel Authentication (CIBA).
+
+The client asks the provider to authenticate a user on their own device with
+`Client::backchannel_authenticate`, then gets the tokens in one of the delivery modes:
+
+- poll: call `Client::backchannel_token` every `interval` seconds until it returns a token
+- ping: parse the callback with `BackchannelNotification::parse`, then call
+  `Client::backchannel_token` once
+- push: parse the callback with `BackchannelNotification::parse` and pass it to
+  `Client::backchannel_push`
+
+See [OpenID Connect CIBA](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html).
+*/
+use crate::{
+    error::{Backchannel, ClientError, Error},
+    Bearer, OAuth2Error,
+};
+use biscuit::jwa::SignatureAlgorithm;
+use ring::{
+    constant_time,
+    digest::{digest, SHA256, SHA384, SHA512},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Parameters of a backchannel authentication request. Derives Default, so remember to
+/// ..Default::default() after you specify what you want. One of the hints is required.
+///
+/// See [CIBA, section 7.1](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.7.1).
+#[derive(Debug, Clone, Default)]
+pub struct BackchannelAuthentication {
+    /// MUST contain openid, which is added if missing.
+    pub scope: Option<String>,
+    pub acr_values: Option<String>,
+    pub login_hint_token: Option<String>,
+    pub id_token_hint: Option<String>,
+    pub login_hint: Option<String>,
+    /// A short message shown on both the consumption and the authentication device, so the
+    /// user can tell the request is theirs.
+    pub binding_message: Option<String>,
+    pub user_code: Option<String>,
+    /// Requested lifetime of the request in seconds.
+    pub requested_expiry: Option<u32>,
+    /// Bearer token the provider sends to the client notification endpoint, required for the
+    /// ping and push modes.
+    pub client_notification_token: Option<String>,
+}
+
+/// The provider's acknowledgement of a backchannel authentication request.
+///
+/// See [CIBA, section 7.3](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.7.3).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BackchannelAuthenticationResponse {
+    pub auth_req_id: String,
+    /// Seconds until the request expires.
+    pub expires_in: u64,
+    /// Seconds to wait between polls. Raised when the provider asks to slow down.
+    #[serde(default)]
+    pub interval: Option<u64>,
+}
+
+impl BackchannelAuthenticationResponse {
+    /// How long to wait before the next poll, 5 seconds if the provider did not say.
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.interval.unwrap_or(5))
+    }
+}
+
+/// A call from the provider to the client notification endpoint.
+///
+/// See [CIBA, section 10](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.10).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackchannelNotification {
+    pub auth_req_id: String,
+    /// The tokens, in push mode only.
+    pub bearer: Option<Bearer>,
+}
+
+impl BackchannelNotification {
+    /// Parses the JSON body of a notification, after checking its `Authorization` header
+    /// carries the `client_notification_token` of the request. Errors are:
+    ///
+    /// - Backchannel::Unauthorized if the header does not carry the token
+    /// - Error::Json if the body is not a notification
+    /// - Error::ClientError with an OAuth2Error if the provider pushed an error
+    pub fn parse(
+        authorization: Option<&str>,
+        body: &[u8],
+        client_notification_token: &str,
+    ) -> Result<Self, Error> {
+        let token = authorization
+            .map(str::trim)
+            .and_then(|authorization| authorization.strip_prefix("Bearer "))
+            .ok_or(Backchannel::Unauthorized)?;
+        // The token is a secret, compare it without leaking how much of it matched
+        constant_time::verify_slices_are_equal(
+            token.trim_start().as_bytes(),
+            client_notification_token.as_bytes(),
+        )
+        .map_err(|_| Backchannel::Unauthorized)?;
+
+        let json: Value = serde_json::from_slice(body)?;
+        if json.get("error").is_some() {
+            let error: OAuth2Error = serde_json::from_value(json)?;
+            return Err(ClientError::from(error).into());
+        }
+        let auth_req_id = match json.get("auth_req_id").and_then(Value::as_str) {
+            Some(auth_req_id) => auth_req_id.to_string(),
+            None => return Err(Backchannel::NoToken.into()),
+        };
+        let bearer = if json.get("access_token").is_some() {
+            Some(serde_json::from_value(json)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            auth_req_id,
+            bearer,
+        })
+    }
+}
+
+/// The claims binding a pushed ID token to the request and its tokens.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct PushClaims {
+    #[serde(rename = "urn:openid:params:jwt:claim:auth_req_id", default)]
+    pub(crate) auth_req_id: Option<String>,
+    #[serde(default)]
+    pub(crate) at_hash: Option<String>,
+    #[serde(default)]
+    pub(crate) rt_hash: Option<String>,
+}
+
+/// The binding claims of a pushed ID token, read without verification, so only call it after
+/// the token is validated.
+pub(crate) fn push_claims(id_token: &str) -> PushClaims {
+    let payload = id_token.split('.').nth(1).unwrap_or_default();
+    base64::decode_config(payload, base64::URL_SAFE_NO_PAD)
+        .ok()
+        .and_then(|payload| serde_json::from_slice(&payload).ok())
+        .unwrap_or_default()
+}
+
+/// The `at_hash` or `rt_hash` of a token: the left half of its hash with the hash algorithm of
+/// the ID token's alg, base64url encoded. None for algorithms without a hash.
+///
+/// See [CIBA, section 10.3.1](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.10.3.1).
+pub(crate) fn token_hash(algorithm: SignatureAlgorithm, token: &str) -> Option<String> {
+    let hash = match algorithm {
+        SignatureAlgorithm::HS256
+        | SignatureAlgorithm::RS256
+        | SignatureAlgorithm::PS256
+        | SignatureAlgorithm::ES256 => &SHA256,
+        SignatureAlgorithm::HS384
+        | SignatureAlgorithm::RS384
+        | SignatureAlgorithm::PS384
+        | SignatureAlgorithm::ES384 => &SHA384,
+        SignatureAlgorithm::HS512
+        | SignatureAlgorithm::RS512
+        | SignatureAlgorithm::PS512
+        | SignatureAlgorithm::ES512 => &SHA512,
+        SignatureAlgorithm::None => return None,
+    };
+    let hash = digest(hash, token.as_bytes());
+    let half = &hash.as_ref()[..hash.as_ref().len() / 2];
+    Some(base64::encode_config(half, base64::URL_SAFE_NO_PAD))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BackchannelNotification;
+    use crate::error::{Backchannel, Error};
+
+    #[test]
+    fn ping_notification() {
+        let notification = BackchannelNotification::parse(
+            Some("Bearer 8d67dc78-7faa-4d41-aabd-67707b374255"),
+            br#"{ "auth_req_id": "1c266114-a1be-4252-8ad1-04986c5b9ac1" }"#,
+            "8d67dc78-7faa-4d41-aabd-67707b374255",
+        )
+        .unwrap();
+        assert_eq!(
+            "1c266114-a1be-4252-8ad1-04986c5b9ac1",
+            notification.auth_req_id
+        );
+        assert!(notification.bearer.is_none());
+    }
+
+    #[test]
+    fn push_notification() {
+        let notification = BackchannelNotification::parse(
+            Some("Bearer token"),
+            br#"{
+                "auth_req_id": "id",
+                "access_token": "G5kXH2wHvUra0sHlDy1iTkDJgsgUO1bN",
+                "token_type": "Bearer",
+                "expires_in": 120
+            }"#,
+            "token",
+        )
+        .unwrap();
+        let bearer = notification.bearer.unwrap();
+        assert_eq!("G5kXH2wHvUra0sHlDy1iTkDJgsgUO1bN", bearer.access_token);
+    }
+
+    #[test]
+    fn unauthorized_notification() {
+        let body = br#"{ "auth_req_id": "id" }"#;
+        for authorization in &[
+            None,
+            Some("Bearer tokem"),
+            Some("Bearer toke"),
+            Some("token"),
+        ] {
+            match BackchannelNotification::parse(*authorization, body, "token") {
+                Err(Error::Backchannel(Backchannel::Unauthorized)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+}
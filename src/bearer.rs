@@ -1,3 +1,4 @@
+use crate::{AuthorizationDetail, TokenType};
 use chrono::{DateTime, Duration, Utc};
 use serde::{de::Visitor, ser::Serializer, Deserialize, Deserializer, Serialize};
 use std::fmt;
@@ -8,6 +9,15 @@ use std::fmt;
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct Bearer {
     pub access_token: String,
+    /// `Bearer`, or `DPoP` if the token is bound to the client's DPoP key.
+    #[serde(default)]
+    pub token_type: Option<String>,
+    /// The type of the token issued by a token exchange.
+    #[serde(default)]
+    pub issued_token_type: Option<TokenType>,
+    /// The permissions granted in a rich authorization request.
+    #[serde(default)]
+    pub authorization_details: Option<Vec<AuthorizationDetail>>,
     pub scope: Option<String>,
     pub refresh_token: Option<String>,
     #[serde(
@@ -63,6 +73,15 @@ fn serialize_expire_in<S: Serializer>(
 }
 
 impl Bearer {
+    /// Whether the token is bound to a DPoP key and must be sent with a proof.
+    ///
+    /// See [RFC 9449, section 5](https://tools.ietf.org/html/rfc9449#section-5).
+    pub fn is_dpop(&self) -> bool {
+        self.token_type
+            .as_ref()
+            .is_some_and(|token_type| token_type.eq_ignore_ascii_case("DPoP"))
+    }
+
     pub fn expired(&self) -> bool {
         if let Some(expires) = self.expires {
             expires < Utc::now()
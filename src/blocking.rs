@@ -0,0 +1,380 @@
+/*!
+Blocking OpenID Connect client.
+
+Same operations as the async [`Client`](../struct.Client.html), with synchronous signatures and
+no async runtime needed. Token decoding and validation are shared with the async client, reach
+them (and everything else that does not do I/O) through `Deref`. The async methods reachable
+that way send with the blocking transport, so call the synchronous ones of this client instead.
+*/
+use crate::{
+    backchannel::{BackchannelAuthentication, BackchannelAuthenticationResponse},
+    error::{ClientError, Error, Http},
+    http_client::{HttpRequest, HttpResponse},
+    AssertionKey, Bearer, Claims, ClaimsResolver, Discovered, DiscoveryOptions, HttpClient,
+    Introspection, Metadata, Provider, StandardClaims, Token, TokenExchange, Userinfo,
+    ValidationOptions,
+};
+use async_trait::async_trait;
+use biscuit::{jwk::JWKSet, CompactJson, Empty};
+use futures_executor::block_on;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::ops::{Deref, DerefMut};
+use url::Url;
+
+/// HTTP clients the blocking client can send requests with: a `reqwest::blocking::Client`, or
+/// any `HttpClient` that does not need an async runtime, which is driven on the current thread.
+///
+/// The async [`Client`](../struct.Client.html) never takes a transport that blocks:
+///
+/// ```compile_fail
+/// use openid::{provider::google::Installed, Client};
+///
+/// let client: Client<_> = Client::new(
+///     Installed,
+///     String::from("CLIENT_ID"),
+///     String::from("CLIENT_SECRET"),
+///     None,
+///     reqwest::blocking::Client::new(), None,
+/// );
+/// ```
+pub trait BlockingHttpClient {
+    /// The transport the blocking client sends requests with.
+    fn into_transport(self) -> BlockingTransport;
+}
+
+impl BlockingHttpClient for reqwest::blocking::Client {
+    fn into_transport(self) -> BlockingTransport {
+        BlockingTransport(Box::new(BlockingReqwest(self)))
+    }
+}
+
+impl<T: HttpClient + 'static> BlockingHttpClient for T {
+    fn into_transport(self) -> BlockingTransport {
+        BlockingTransport(Box::new(self))
+    }
+}
+
+/// The transport of a blocking client. It is not an `HttpClient`, so it cannot end up in an
+/// async client.
+#[derive(Debug)]
+pub struct BlockingTransport(Box<dyn HttpClient>);
+
+/// Sends requests with a blocking reqwest client. Sending blocks the current thread, so only
+/// the blocking client builds it, and drives it without an async runtime.
+#[derive(Debug)]
+struct BlockingReqwest(reqwest::blocking::Client);
+
+#[async_trait]
+impl HttpClient for BlockingReqwest {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Http> {
+        let resp = self
+            .0
+            .request(request.method, request.url)
+            .headers(request.headers)
+            .body(request.body)
+            .send()?;
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let body = resp.bytes()?.to_vec();
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// Blocking OAuth 2.0 client.
+#[derive(Debug)]
+pub struct Client<P = Discovered, C: CompactJson + Claims = StandardClaims> {
+    inner: crate::Client<P, C>,
+}
+
+impl<C: CompactJson + Claims> Client<Discovered, C> {
+    /// Constructs a client from an issuer url and client parameters via discovery
+    pub fn discover(
+        id: String,
+        secret: String,
+        redirect: Option<String>,
+        issuer: Url,
+    ) -> Result<Self, Error> {
+        Self::discover_with_options(id, secret, redirect, issuer, &DiscoveryOptions::default())
+    }
+
+    /// Constructs a client from an issuer url and client parameters via discovery, using the
+    /// given discovery options.
+    pub fn discover_with_options(
+        id: String,
+        secret: String,
+        redirect: Option<String>,
+        issuer: Url,
+        options: &DiscoveryOptions,
+    ) -> Result<Self, Error> {
+        let http_client = reqwest::blocking::Client::new();
+        Self::discover_with_client(id, secret, redirect, issuer, http_client, options)
+    }
+
+    /// Constructs a client from an issuer url and client parameters via discovery, sending all
+    /// requests with the given HTTP client. The client is driven on the current thread, so it
+    /// must not need an async runtime.
+    pub fn discover_with_client(
+        id: String,
+        secret: String,
+        redirect: Option<String>,
+        issuer: Url,
+        http_client: impl BlockingHttpClient,
+        options: &DiscoveryOptions,
+    ) -> Result<Self, Error> {
+        block_on(crate::Client::discover_with_client(
+            id,
+            secret,
+            redirect,
+            issuer,
+            http_client.into_transport().0,
+            options,
+        ))
+        .map(Self::from)
+    }
+
+    /// Constructs a client from a previously exported metadata snapshot, without any network
+    /// requests.
+    pub fn from_metadata(
+        id: String,
+        secret: String,
+        redirect: Option<String>,
+        http_client: impl BlockingHttpClient,
+        metadata: Metadata,
+    ) -> Result<Self, Error> {
+        let http_client = http_client.into_transport().0;
+        crate::Client::from_metadata(id, secret, redirect, http_client, metadata).map(Self::from)
+    }
+
+    /// Refreshes the discovered config and key set once their HTTP cache lifetime is over.
+    pub fn refresh(&mut self) -> Result<bool, Error> {
+        block_on(self.inner.refresh())
+    }
+
+    /// Given an auth_code and validation options, request the token, decode, and validate it.
+    /// Without options the client's `validation` is used.
+    pub fn authenticate(
+        &self,
+        auth_code: &str,
+        options: Option<&ValidationOptions>,
+    ) -> Result<Token<C>, Error> {
+        block_on(self.inner.authenticate(auth_code, options))
+    }
+
+    /// Requests a token with the resource owner's username and password. Needs the
+    /// `password-grant` feature.
+    #[cfg(feature = "password-grant")]
+    pub fn request_token_password(
+        &self,
+        username: &str,
+        password: &str,
+        scope: Option<&str>,
+    ) -> Result<Token<C>, Error> {
+        block_on(self.inner.request_token_password(username, password, scope))
+    }
+
+    /// Asks the provider to authenticate a user on their own device.
+    pub fn backchannel_authenticate(
+        &self,
+        request: &BackchannelAuthentication,
+    ) -> Result<BackchannelAuthenticationResponse, Error> {
+        block_on(self.inner.backchannel_authenticate(request))
+    }
+
+    /// Asks the token endpoint for the tokens of a backchannel authentication, None while the
+    /// user has not answered yet.
+    pub fn backchannel_token(
+        &self,
+        response: &mut BackchannelAuthenticationResponse,
+    ) -> Result<Option<Token<C>>, Error> {
+        block_on(self.inner.backchannel_token(response))
+    }
+
+    /// Get a userinfo json document for a given token at the provider's userinfo endpoint.
+    pub fn request_userinfo(&self, token: &Token<C>) -> Result<Userinfo, Error> {
+        block_on(self.inner.request_userinfo(token))
+    }
+
+    /// Resolves the aggregated and distributed claims of a userinfo response or ID token.
+    pub fn resolve_claims<T: Serialize>(
+        &self,
+        resolver: &ClaimsResolver,
+        claims: &T,
+    ) -> Result<Map<String, Value>, Error> {
+        block_on(self.inner.resolve_claims(resolver, claims))
+    }
+
+    /// Ask the provider's introspection endpoint about a token.
+    pub fn introspect(
+        &self,
+        token: &str,
+        token_type_hint: Option<&str>,
+    ) -> Result<Introspection, Error> {
+        block_on(self.inner.introspect(token, token_type_hint))
+    }
+}
+
+impl<P, C> Client<P, C>
+where
+    P: Provider,
+    C: CompactJson + Claims,
+{
+    /// Creates a client.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use openid::{blocking::Client, StandardClaims};
+    /// use openid::provider::google::Installed;
+    ///
+    /// let client: Client<_, StandardClaims> = Client::new(
+    ///     Installed,
+    ///     String::from("CLIENT_ID"),
+    ///     String::from("CLIENT_SECRET"),
+    ///     Some(String::from("urn:ietf:wg:oauth:2.0:oob")),
+    ///     reqwest::blocking::Client::new(), None,
+    /// );
+    /// ```
+    pub fn new(
+        provider: P,
+        client_id: String,
+        client_secret: String,
+        redirect_uri: Option<String>,
+        http_client: impl BlockingHttpClient,
+        jwks: Option<JWKSet<Empty>>,
+    ) -> Self {
+        crate::Client::new(
+            provider,
+            client_id,
+            client_secret,
+            redirect_uri,
+            http_client.into_transport().0,
+            jwks,
+        )
+        .into()
+    }
+
+    /// Requests an access token using an authorization code.
+    pub fn request_token(&self, code: &str) -> Result<Bearer, ClientError> {
+        block_on(self.inner.request_token(code))
+    }
+
+    /// Requests an access token using an authorization code, restricted to the given resources.
+    pub fn request_token_with_resources(
+        &self,
+        code: &str,
+        resources: &[&str],
+    ) -> Result<Bearer, ClientError> {
+        block_on(self.inner.request_token_with_resources(code, resources))
+    }
+
+    /// Refreshes an access token.
+    pub fn refresh_token(&self, token: Bearer, scope: Option<&str>) -> Result<Bearer, ClientError> {
+        block_on(self.inner.refresh_token(token, scope))
+    }
+
+    /// Requests an access token with a JWT assertion signed by the key.
+    pub fn request_token_jwt_bearer(
+        &self,
+        key: &AssertionKey,
+        subject: &str,
+        scope: Option<&str>,
+    ) -> Result<Bearer, ClientError> {
+        block_on(self.inner.request_token_jwt_bearer(key, subject, scope))
+    }
+
+    /// Exchanges a token for another one.
+    pub fn exchange_token(&self, exchange: &TokenExchange) -> Result<Bearer, ClientError> {
+        block_on(self.inner.exchange_token(exchange))
+    }
+
+    /// Refreshes an access token, restricted to the given resources.
+    pub fn refresh_token_with_resources(
+        &self,
+        token: Bearer,
+        scope: Option<&str>,
+        resources: &[&str],
+    ) -> Result<Bearer, ClientError> {
+        block_on(
+            self.inner
+                .refresh_token_with_resources(token, scope, resources),
+        )
+    }
+
+    /// Ensures an access token is valid by refreshing it if necessary.
+    pub fn ensure_token(&self, token: Bearer) -> Result<Bearer, ClientError> {
+        block_on(self.inner.ensure_token(token))
+    }
+
+    /// Sends a request to a protected resource with the access token, with a DPoP proof if the
+    /// token is DPoP bound.
+    pub fn send_with_token(
+        &self,
+        request: HttpRequest,
+        token: &Bearer,
+    ) -> Result<HttpResponse, Error> {
+        block_on(self.inner.send_with_token(request, token))
+    }
+}
+
+impl<P, C: CompactJson + Claims> From<crate::Client<P, C>> for Client<P, C> {
+    fn from(inner: crate::Client<P, C>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<P, C: CompactJson + Claims> Deref for Client<P, C> {
+    type Target = crate::Client<P, C>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<P, C: CompactJson + Claims> DerefMut for Client<P, C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Client;
+    use crate::{http_client::mock::MockHttpClient, DiscoveryOptions};
+    use url::Url;
+
+    #[test]
+    fn discover() {
+        let http_client = MockHttpClient::default()
+            .with_json(
+                "https://example.com/.well-known/openid-configuration",
+                r#"{
+                    "issuer": "https://example.com/",
+                    "authorization_endpoint": "https://example.com/auth",
+                    "token_endpoint": "https://example.com/token",
+                    "jwks_uri": "https://example.com/certs",
+                    "response_types_supported": ["code"],
+                    "subject_types_supported": ["public"],
+                    "id_token_signing_alg_values_supported": ["RS256"]
+                }"#,
+            )
+            .with_json("https://example.com/certs", r#"{ "keys": [] }"#);
+        let client: Client = Client::discover_with_client(
+            String::from("foo"),
+            String::from("bar"),
+            None,
+            Url::parse("https://example.com").unwrap(),
+            http_client,
+            &DiscoveryOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            "https://example.com/token",
+            client.config().token_endpoint.as_str()
+        );
+    }
+}
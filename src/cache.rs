@@ -0,0 +1,177 @@
+use crate::{
+    error::Error,
+    http_client::{HttpClient, HttpRequest},
+};
+use chrono::{DateTime, Duration, Utc};
+use http::{
+    header::{
+        HeaderMap, AGE, CACHE_CONTROL, ETAG, EXPIRES, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+        LAST_MODIFIED,
+    },
+    StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use url::Url;
+
+/// HTTP caching information of a fetched document, taken from the `Cache-Control`, `Expires`,
+/// `ETag` and `Last-Modified` response headers.
+///
+/// See [RFC 7234](https://tools.ietf.org/html/rfc7234) and
+/// [RFC 7232](https://tools.ietf.org/html/rfc7232).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CacheControl {
+    /// When the document becomes stale. None if the server gave no freshness information, in
+    /// which case the document is always considered stale.
+    #[serde(default)]
+    pub expires: Option<DateTime<Utc>>,
+    /// Entity tag to send with `If-None-Match`.
+    #[serde(default)]
+    pub etag: Option<String>,
+    /// Last modification date to send with `If-Modified-Since`.
+    #[serde(default)]
+    pub last_modified: Option<String>,
+}
+
+impl CacheControl {
+    /// Reads the caching information from response headers.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+        let now = Utc::now();
+
+        let mut expires = None;
+        if let Some(cache_control) = header(CACHE_CONTROL) {
+            for directive in cache_control.split(',').map(str::trim) {
+                let directive = directive.to_ascii_lowercase();
+                if directive == "no-cache" || directive == "no-store" {
+                    expires = Some(now);
+                    break;
+                }
+                if let Some(max_age) = directive.strip_prefix("max-age=") {
+                    if let Ok(max_age) = max_age.trim_matches('"').parse::<i64>() {
+                        let age = header(AGE)
+                            .and_then(|age| age.parse::<i64>().ok())
+                            .unwrap_or(0);
+                        expires = Some(now + Duration::seconds(max_age - age));
+                    }
+                }
+            }
+        }
+        if expires.is_none() {
+            expires = header(EXPIRES)
+                .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+                .map(|date| date.with_timezone(&Utc));
+        }
+
+        Self {
+            expires,
+            etag: header(ETAG).map(str::to_string),
+            last_modified: header(LAST_MODIFIED).map(str::to_string),
+        }
+    }
+
+    /// Whether the document can still be used without asking the server.
+    pub fn is_fresh(&self) -> bool {
+        match self.expires {
+            Some(expires) => expires > Utc::now(),
+            None => false,
+        }
+    }
+}
+
+/// Result of a conditional request.
+pub(crate) enum Fetched<T> {
+    Modified(T, CacheControl),
+    /// The server answered 304, the cached document is still valid.
+    NotModified(CacheControl),
+}
+
+/// Get a JSON document, sending the validators of the cached copy if there is one.
+pub(crate) async fn fetch<T: DeserializeOwned>(
+    client: &dyn HttpClient,
+    url: Url,
+    cached: Option<&CacheControl>,
+) -> Result<Fetched<T>, Error> {
+    let mut request = HttpRequest::get(url);
+    if let Some(cached) = cached {
+        if let Some(ref etag) = cached.etag {
+            request = request.header(IF_NONE_MATCH, etag)?;
+        }
+        if let Some(ref last_modified) = cached.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified)?;
+        }
+    }
+    let resp = client.send(request).await?;
+
+    if resp.status == StatusCode::NOT_MODIFIED {
+        if let Some(cached) = cached {
+            let mut cache = CacheControl::from_headers(&resp.headers);
+            // A 304 may omit validators that did not change
+            if cache.etag.is_none() {
+                cache.etag = cached.etag.clone();
+            }
+            if cache.last_modified.is_none() {
+                cache.last_modified = cached.last_modified.clone();
+            }
+            return Ok(Fetched::NotModified(cache));
+        }
+    }
+
+    let resp = resp.error_for_status()?;
+    let cache = CacheControl::from_headers(&resp.headers);
+    let document = resp.json()?;
+    Ok(Fetched::Modified(document, cache))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CacheControl;
+    use chrono::{Duration, TimeZone, Utc};
+    use http::header::{HeaderMap, HeaderValue, AGE, CACHE_CONTROL, ETAG, EXPIRES};
+
+    #[test]
+    fn max_age() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CACHE_CONTROL,
+            HeaderValue::from_static("public, max-age=3600"),
+        );
+        headers.insert(AGE, HeaderValue::from_static("600"));
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+        let cache = CacheControl::from_headers(&headers);
+        let expires = cache.expires.unwrap();
+        assert!(expires > Utc::now() + Duration::seconds(2990));
+        assert!(expires <= Utc::now() + Duration::seconds(3000));
+        assert!(cache.is_fresh());
+        assert_eq!(Some("\"abc\"".to_string()), cache.etag);
+    }
+
+    #[test]
+    fn no_cache_wins_over_expires() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        headers.insert(
+            EXPIRES,
+            HeaderValue::from_static("Fri, 01 Jan 2100 00:00:00 GMT"),
+        );
+        assert!(!CacheControl::from_headers(&headers).is_fresh());
+    }
+
+    #[test]
+    fn expires() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            EXPIRES,
+            HeaderValue::from_static("Fri, 01 Jan 2100 00:00:00 GMT"),
+        );
+        let cache = CacheControl::from_headers(&headers);
+        assert_eq!(
+            Utc.with_ymd_and_hms(2100, 1, 1, 0, 0, 0).single(),
+            cache.expires
+        );
+    }
+
+    #[test]
+    fn no_information_is_stale() {
+        assert!(!CacheControl::from_headers(&HeaderMap::new()).is_fresh());
+    }
+}
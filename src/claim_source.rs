@@ -0,0 +1,286 @@
+use crate::{
+    client::decode_jws,
+    error::{ClaimSource as ErrorClaimSource, Error},
+    http_client::HttpRequest,
+    Claims, Client, Discovered, HttpClient,
+};
+use biscuit::{jwa::SignatureAlgorithm, jwk::JWKSet, jws::Compact, CompactJson, Empty};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use url::Url;
+
+/// Where aggregated or distributed claims come from, as listed in `_claim_sources`.
+///
+/// See [OpenID Connect Core, section 5.6.2](https://openid.net/specs/openid-connect-core-1_0.html#AggregatedDistributedClaims).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ClaimSource {
+    /// The claims are in a JWT signed by the claims provider.
+    Aggregated {
+        #[serde(rename = "JWT")]
+        jwt: String,
+    },
+    /// The claims are fetched from the endpoint of the claims provider, which returns a JWT.
+    Distributed {
+        endpoint: Url,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        access_token: Option<String>,
+    },
+}
+
+/// Claims of a JWT from a claims provider.
+#[derive(Debug, Deserialize, Serialize)]
+struct SourceClaims {
+    #[serde(default)]
+    iss: Option<String>,
+    #[serde(flatten)]
+    claims: Map<String, Value>,
+}
+
+impl CompactJson for SourceClaims {}
+
+/// Resolves the aggregated and distributed claims of a userinfo response or ID token into a
+/// single claims map. JWTs from claims providers are only accepted if they are signed by a key
+/// of their issuer given with [`trust`](#method.trust).
+#[derive(Debug, Default)]
+pub struct ClaimsResolver {
+    pub issuers: HashMap<String, JWKSet<Empty>>,
+    /// Signature algorithms the JWTs may use. Empty allows the asymmetric ones.
+    pub algorithms: Vec<SignatureAlgorithm>,
+}
+
+impl ClaimsResolver {
+    /// Creates a resolver trusting the claims providers of the client's provider, signed with
+    /// its keys.
+    pub fn from_client<C: CompactJson + Claims>(client: &Client<Discovered, C>) -> Self {
+        let keys = client.jwks.as_ref().map(|jwks| jwks.keys.clone());
+        let jwks = JWKSet {
+            keys: keys.unwrap_or_default(),
+        };
+        Self::default().trust(client.config().issuer.as_str(), jwks)
+    }
+
+    /// Trusts the JWTs of a claims provider signed by one of the keys.
+    pub fn trust(mut self, issuer: impl Into<String>, jwks: JWKSet<Empty>) -> Self {
+        self.issuers.insert(issuer.into(), jwks);
+        self
+    }
+
+    /// Returns the claims of a userinfo response or ID token with the referenced claims of their
+    /// sources merged in. Any claims with `_claim_names` and `_claim_sources` will do, such as
+    /// `Userinfo`, `StandardClaims` or a custom claims struct. Errors are:
+    ///
+    /// - Error::Json if `_claim_names` or `_claim_sources` are malformed
+    /// - ClaimSource::UnknownSource if a claim name refers to a source not in `_claim_sources`
+    /// - ClaimSource::UntrustedIssuer if a source JWT is not from a trusted issuer
+    /// - ClaimSource::MissingClaim if a source JWT does not have the claim referring to it
+    /// - Error::Jose if a source JWT is not signed by a key of its issuer
+    /// - Error::Http if a distributed claims endpoint cannot be reached or returns an error
+    pub async fn resolve<T: Serialize>(
+        &self,
+        http_client: &dyn HttpClient,
+        claims: &T,
+    ) -> Result<Map<String, Value>, Error> {
+        let mut claims = match serde_json::to_value(claims)? {
+            Value::Object(claims) => claims,
+            _ => Map::new(),
+        };
+        let claim_names: HashMap<String, String> = take(&mut claims, "_claim_names")?;
+        let claim_sources: HashMap<String, ClaimSource> = take(&mut claims, "_claim_sources")?;
+
+        let mut sources: HashMap<&str, Map<String, Value>> = HashMap::new();
+        for (name, source_name) in &claim_names {
+            if !sources.contains_key(source_name.as_str()) {
+                let source = claim_sources
+                    .get(source_name)
+                    .ok_or_else(|| ErrorClaimSource::UnknownSource(source_name.clone()))?;
+                let jwt = match *source {
+                    ClaimSource::Aggregated { ref jwt } => jwt.clone(),
+                    ClaimSource::Distributed {
+                        ref endpoint,
+                        ref access_token,
+                    } => {
+                        let mut request = HttpRequest::get(endpoint.clone());
+                        if let Some(ref access_token) = access_token {
+                            request = request.bearer_auth(access_token)?;
+                        }
+                        let resp = http_client.send(request).await?.error_for_status()?;
+                        String::from_utf8_lossy(&resp.body).trim().to_string()
+                    }
+                };
+                sources.insert(source_name, self.verify(&jwt)?);
+            }
+            let value = sources[source_name.as_str()].get(name).cloned();
+            let value = value.ok_or_else(|| ErrorClaimSource::MissingClaim {
+                source: source_name.clone(),
+                claim: name.clone(),
+            })?;
+            claims.insert(name.clone(), value);
+        }
+        Ok(claims)
+    }
+
+    /// Verifies a JWT of a claims provider with the keys of its issuer.
+    fn verify(&self, jwt: &str) -> Result<Map<String, Value>, Error> {
+        let mut token: Compact<SourceClaims, Empty> = Compact::new_encoded(jwt);
+        let issuer = token.unverified_payload()?.iss.unwrap_or_default();
+        let jwks = self
+            .issuers
+            .get(&issuer)
+            .ok_or(ErrorClaimSource::UntrustedIssuer(issuer))?;
+        let algorithms = Some(self.algorithms.as_slice()).filter(|algs| !algs.is_empty());
+        decode_jws(jwks, &mut token, algorithms)?;
+        Ok(token.unwrap_decoded().1.claims)
+    }
+}
+
+/// Removes a claim and deserializes it, the default if it is missing or null.
+fn take<T: DeserializeOwned + Default>(
+    claims: &mut Map<String, Value>,
+    name: &str,
+) -> Result<T, Error> {
+    match claims.remove(name) {
+        Some(Value::Null) | None => Ok(T::default()),
+        Some(value) => Ok(serde_json::from_value(value)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClaimsResolver;
+    use crate::{
+        error::{ClaimSource, Error},
+        http_client::mock::MockHttpClient,
+        StandardClaims, Userinfo,
+    };
+    use biscuit::{
+        jwa::SignatureAlgorithm,
+        jwk::JWKSet,
+        jws::{Compact, RegisteredHeader, Secret},
+        ClaimsSet, Empty, RegisteredClaims,
+    };
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    const ISSUER: &str = "https://claims.example.com";
+
+    fn resolver() -> ClaimsResolver {
+        let jwks: JWKSet<Empty> = serde_json::from_str(
+            r#"{ "keys": [{ "kty": "oct", "kid": "key", "k": "c2VjcmV0" }] }"#,
+        )
+        .unwrap();
+        let mut resolver = ClaimsResolver::default().trust(ISSUER, jwks);
+        resolver.algorithms = vec![SignatureAlgorithm::HS256];
+        resolver
+    }
+
+    fn jwt(issuer: &str, mut claims: Value) -> String {
+        let header = RegisteredHeader {
+            algorithm: SignatureAlgorithm::HS256,
+            key_id: Some(String::from("key")),
+            ..Default::default()
+        };
+        claims["iss"] = Value::from(issuer);
+        let claims = ClaimsSet {
+            registered: RegisteredClaims::default(),
+            private: claims,
+        };
+        let secret = Secret::Bytes(b"secret".to_vec());
+        Compact::new_decoded(header.into(), claims)
+            .into_encoded(&secret)
+            .unwrap()
+            .unwrap_encoded()
+            .to_string()
+    }
+
+    fn userinfo(aggregated: &str) -> Userinfo {
+        serde_json::from_value(json!({
+            "sub": "248289761001",
+            "_claim_names": {
+                "address": "src1",
+                "payment_info": "src2",
+                "shipping_address": "src2"
+            },
+            "_claim_sources": {
+                "src1": { "JWT": aggregated },
+                "src2": {
+                    "endpoint": "https://bank.example.com/claim_source",
+                    "access_token": "ksj3n283dke"
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn resolve() {
+        let aggregated = jwt(ISSUER, json!({ "address": { "country": "US" } }));
+        let distributed = jwt(
+            ISSUER,
+            json!({ "payment_info": "Some_Card", "shipping_address": { "country": "DE" } }),
+        );
+        let http_client = Arc::new(MockHttpClient::default().with_response(
+            "https://bank.example.com/claim_source",
+            crate::http_client::HttpResponse {
+                status: http::StatusCode::OK,
+                headers: Default::default(),
+                body: distributed.into_bytes(),
+            },
+        ));
+
+        let userinfo = userinfo(&aggregated);
+        let claims =
+            futures::executor::block_on(resolver().resolve(&http_client, &userinfo)).unwrap();
+        assert_eq!("248289761001", claims["sub"]);
+        assert_eq!("US", claims["address"]["country"]);
+        assert_eq!("Some_Card", claims["payment_info"]);
+        assert_eq!("DE", claims["shipping_address"]["country"]);
+        assert!(claims.get("_claim_names").is_none());
+
+        // Both claims of the distributed source come from a single request
+        let requests = http_client.requests.lock().unwrap();
+        assert_eq!(1, requests.len());
+        assert_eq!(
+            "Bearer ksj3n283dke",
+            requests[0].headers[http::header::AUTHORIZATION]
+        );
+    }
+
+    #[test]
+    fn resolve_id_token() {
+        let aggregated = jwt(ISSUER, json!({ "address": { "country": "US" } }));
+        let claims: StandardClaims = serde_json::from_value(json!({
+            "iss": "https://example.com/",
+            "sub": "248289761001",
+            "aud": "client",
+            "exp": 1311281970,
+            "iat": 1311280970,
+            "_claim_names": { "address": "src1" },
+            "_claim_sources": { "src1": { "JWT": aggregated } }
+        }))
+        .unwrap();
+        let http_client = MockHttpClient::default();
+        let claims =
+            futures::executor::block_on(resolver().resolve(&http_client, &claims)).unwrap();
+        assert_eq!("https://example.com/", claims["iss"]);
+        assert_eq!("248289761001", claims["sub"]);
+        assert_eq!("client", claims["aud"]);
+        assert_eq!("US", claims["address"]["country"]);
+        assert!(claims.get("_claim_sources").is_none());
+    }
+
+    #[test]
+    fn untrusted_issuer() {
+        let aggregated = jwt("https://evil.example.com", json!({ "address": {} }));
+        let mut userinfo = userinfo(&aggregated);
+        userinfo.claim_names.retain(|_, source| source == "src1");
+        let http_client = MockHttpClient::default();
+        match futures::executor::block_on(resolver().resolve(&http_client, &userinfo)) {
+            Err(Error::ClaimSource(ClaimSource::UntrustedIssuer(issuer))) => {
+                assert_eq!("https://evil.example.com", issuer)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use crate::error::{Error, Mismatch, Missing, Validation};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// How a single claim is asked for. An empty request (the default) asks for the claim as a
+/// voluntary one.
+///
+/// See [OpenID Connect Core, section 5.5.1](https://openid.net/specs/openid-connect-core-1_0.html#IndividualClaimsRequests).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct ClaimRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub essential: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<Value>>,
+}
+
+impl ClaimRequest {
+    /// Marks the claim as needed for the authorization to succeed.
+    pub fn essential(mut self) -> Self {
+        self.essential = Some(true);
+        self
+    }
+
+    /// Asks for the claim to have this exact value.
+    pub fn value(mut self, value: impl Into<Value>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    /// Asks for the claim to have one of these values, in order of preference.
+    pub fn values<V: Into<Value>>(mut self, values: impl IntoIterator<Item = V>) -> Self {
+        self.values = Some(values.into_iter().map(Into::into).collect());
+        self
+    }
+
+    fn is_essential(&self) -> bool {
+        self.essential == Some(true)
+    }
+
+    /// The values the claim must have, if any were requested.
+    fn expected(&self) -> Option<Vec<&Value>> {
+        match (&self.value, &self.values) {
+            (Some(value), _) => Some(vec![value]),
+            (None, Some(values)) => Some(values.iter().collect()),
+            (None, None) => None,
+        }
+    }
+
+    fn verify(&self, name: &str, claims: &Value) -> Result<(), Error> {
+        if !self.is_essential() {
+            return Ok(());
+        }
+        let expected = match self.expected() {
+            Some(expected) => expected,
+            None => return Ok(()),
+        };
+        let actual = match claims.get(name) {
+            Some(actual) if !actual.is_null() => actual,
+            _ => return Err(Validation::Missing(Missing::Claim(name.to_string())).into()),
+        };
+        if expected.contains(&actual) {
+            Ok(())
+        } else {
+            let expected = Value::Array(expected.into_iter().cloned().collect());
+            Err(Validation::Mismatch(Mismatch::Claim {
+                name: name.to_string(),
+                expected: expected.to_string(),
+                actual: actual.to_string(),
+            })
+            .into())
+        }
+    }
+}
+
+/// The `claims` authorization request parameter, asking for individual claims in the ID token
+/// and in the userinfo response. Claims that are asked for without a [`ClaimRequest`] are sent
+/// as `null`.
+///
+/// See [OpenID Connect Core, section 5.5](https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter).
+///
+/// # Examples
+///
+/// ```
+/// use openid::{ClaimRequest, ClaimsRequest};
+///
+/// let claims = ClaimsRequest::default()
+///     .id_token_claim("acr", ClaimRequest::default().essential().value("urn:mace:incommon:iap:silver"))
+///     .userinfo_claim("email", None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct ClaimsRequest {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub userinfo: BTreeMap<String, Option<ClaimRequest>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub id_token: BTreeMap<String, Option<ClaimRequest>>,
+}
+
+impl ClaimsRequest {
+    /// Asks for a claim in the ID token.
+    pub fn id_token_claim(
+        mut self,
+        name: impl Into<String>,
+        request: impl Into<Option<ClaimRequest>>,
+    ) -> Self {
+        self.id_token.insert(name.into(), request.into());
+        self
+    }
+
+    /// Asks for a claim in the userinfo response.
+    pub fn userinfo_claim(
+        mut self,
+        name: impl Into<String>,
+        request: impl Into<Option<ClaimRequest>>,
+    ) -> Self {
+        self.userinfo.insert(name.into(), request.into());
+        self
+    }
+
+    /// Checks that the essential ID token claims asked for with a value or values came back with
+    /// one of them. Pass the decoded claims of the ID token.
+    pub fn verify_id_token<T: Serialize>(&self, claims: &T) -> Result<(), Error> {
+        verify(&self.id_token, claims)
+    }
+
+    /// Checks that the essential userinfo claims asked for with a value or values came back with
+    /// one of them.
+    pub fn verify_userinfo<T: Serialize>(&self, userinfo: &T) -> Result<(), Error> {
+        verify(&self.userinfo, userinfo)
+    }
+}
+
+fn verify<T: Serialize>(
+    requests: &BTreeMap<String, Option<ClaimRequest>>,
+    claims: &T,
+) -> Result<(), Error> {
+    let claims = serde_json::to_value(claims)?;
+    for (name, request) in requests {
+        if let Some(request) = request {
+            request.verify(name, &claims)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClaimRequest, ClaimsRequest};
+    use crate::error::{Error, Mismatch, Missing, Validation};
+    use serde_json::json;
+
+    fn request() -> ClaimsRequest {
+        ClaimsRequest::default()
+            .userinfo_claim("given_name", ClaimRequest::default().essential())
+            .userinfo_claim("email", None)
+            .id_token_claim("auth_time", ClaimRequest::default().essential())
+            .id_token_claim(
+                "acr",
+                ClaimRequest::default().values(vec!["urn:mace:incommon:iap:silver", "gold"]),
+            )
+            .id_token_claim(
+                "sub",
+                ClaimRequest::default().essential().value("248289761001"),
+            )
+    }
+
+    #[test]
+    fn serialize() {
+        assert_eq!(
+            json!({
+                "userinfo": {
+                    "given_name": { "essential": true },
+                    "email": null
+                },
+                "id_token": {
+                    "auth_time": { "essential": true },
+                    "acr": { "values": ["urn:mace:incommon:iap:silver", "gold"] },
+                    "sub": { "essential": true, "value": "248289761001" }
+                }
+            }),
+            serde_json::to_value(request()).unwrap()
+        );
+    }
+
+    #[test]
+    fn verify() {
+        let request = request();
+        // Voluntary and value-less claims are not checked
+        request
+            .verify_id_token(&json!({ "sub": "248289761001", "acr": "bronze" }))
+            .unwrap();
+        request.verify_userinfo(&json!({})).unwrap();
+
+        match request.verify_id_token(&json!({ "sub": "someone else" })) {
+            Err(Error::Validation(Validation::Mismatch(Mismatch::Claim { name, .. }))) => {
+                assert_eq!("sub", name)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match request.verify_id_token(&json!({})) {
+            Err(Error::Validation(Validation::Missing(Missing::Claim(name)))) => {
+                assert_eq!("sub", name)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
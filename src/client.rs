@@ -1,22 +1,40 @@
 use crate::{
-    discovered,
+    backchannel::{
+        self, BackchannelAuthentication, BackchannelAuthenticationResponse, BackchannelNotification,
+    },
+    confirmation::{self, ConfirmationClaims},
+    discovered::{self, MetadataCache},
+    dpop::{DPOP, DPOP_NONCE},
     error::{
-        ClientError, Decode, Error, Expiry, Jose, Mismatch, Missing, Userinfo as ErrorUserinfo,
-        Validation,
+        Backchannel, ClientError, Decode, Error, Expiry, Http, Introspection as ErrorIntrospection,
+        Jose, Mismatch, Missing, Userinfo as ErrorUserinfo, Validation,
     },
-    Bearer, Claims, Config, Discovered, IdToken, OAuth2Error, Options, Provider, StandardClaims,
-    Token, Userinfo,
+    http_client::{HttpClient, HttpRequest, HttpResponse},
+    AssertionKey, Bearer, Claims, ClaimsResolver, ClientAuthMethod, Config, DPoPKey, Discovered,
+    DiscoveryOptions, GrantType, IdToken, Introspection, Metadata, MtlsEndpointAliases,
+    OAuth2Error, OAuth2ErrorCode, Options, Provider, StandardClaims, Token, TokenExchange,
+    Userinfo, ValidationOptions,
 };
 use biscuit::{
     jwa::{self, SignatureAlgorithm},
-    jwk::{AlgorithmParameters, JWKSet},
+    jwk::{AlgorithmParameters, EllipticCurve, JWKSet, KeyOperations, PublicKeyUse, JWK},
     jws::{Compact, Secret},
     CompactJson, Empty, SingleOrMultiple,
 };
-use chrono::{Duration, Utc};
-use reqwest::header::{ACCEPT, CONTENT_TYPE};
-use serde_json::Value;
-use std::marker::PhantomData;
+use chrono::{DateTime, Utc};
+use http::{
+    header::{HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+    Method, StatusCode,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{
+    collections::HashMap,
+    future::Future,
+    marker::PhantomData,
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    time::Duration as StdDuration,
+};
 use url::{form_urlencoded::Serializer, Url};
 
 /// OAuth 2.0 client.
@@ -34,9 +52,22 @@ pub struct Client<P = Discovered, C: CompactJson + Claims = StandardClaims> {
     /// Redirect URI.
     pub redirect_uri: Option<String>,
 
-    pub http_client: reqwest::Client,
+    /// HTTP transport used for all requests. For mutual TLS client authentication it must be
+    /// configured with the client certificate, e.g. with `reqwest::ClientBuilder::identity`.
+    pub http_client: Box<dyn HttpClient>,
+
+    /// How the client authenticates at the provider's endpoints.
+    pub auth_method: ClientAuthMethod,
+
+    /// Key to prove possession of DPoP bound tokens with. Token requests carry a proof when set.
+    pub dpop: Option<DPoPKey>,
+    /// Last `DPoP-Nonce` received, by origin.
+    dpop_nonces: Mutex<HashMap<String, String>>,
 
     pub jwks: Option<JWKSet<Empty>>,
+    /// How ID tokens are validated unless a call is given other options.
+    pub validation: ValidationOptions,
+    cache: Option<MetadataCache>,
     marker: PhantomData<C>,
 }
 
@@ -51,27 +82,299 @@ macro_rules! wrong_key {
     };
 }
 
+/// Algorithms accepted when no allow-list is given. Symmetric ones are left out, so a token
+/// cannot be forged with a key meant for something else.
+pub(crate) const SAFE_ALGORITHMS: &[SignatureAlgorithm] = &[
+    SignatureAlgorithm::RS256,
+    SignatureAlgorithm::RS384,
+    SignatureAlgorithm::RS512,
+    SignatureAlgorithm::PS256,
+    SignatureAlgorithm::PS384,
+    SignatureAlgorithm::PS512,
+    SignatureAlgorithm::ES256,
+    SignatureAlgorithm::ES384,
+];
+
+/// Whether the use and key operations of a key allow verifying signatures with it.
+fn is_signing_key(key: &JWK<Empty>) -> bool {
+    let use_ok = match key.common.public_key_use {
+        None | Some(PublicKeyUse::Signature) => true,
+        Some(_) => false,
+    };
+    let ops_ok = match key.common.key_operations {
+        None => true,
+        Some(ref ops) => ops.contains(&KeyOperations::Verify),
+    };
+    use_ok && ops_ok
+}
+
+/// Decodes an encoded token with the matching key of the key set, if its algorithm is one of
+/// the allowed ones, or one of `SAFE_ALGORITHMS` if no list is given. An empty list allows
+/// nothing. Errors are the ones of `Client::decode_token`.
+pub(crate) fn decode_jws<T: CompactJson>(
+    jwks: &JWKSet<Empty>,
+    token: &mut Compact<T, Empty>,
+    algorithms: Option<&[SignatureAlgorithm]>,
+) -> Result<(), Error> {
+    let header = token.unverified_header()?;
+    let alg = header.registered.algorithm;
+    if alg == SignatureAlgorithm::None {
+        return Err(Decode::Unsigned.into());
+    }
+    if !algorithms.unwrap_or(SAFE_ALGORITHMS).contains(&alg) {
+        return Err(Decode::DisallowedAlgorithm(format!("{:?}", alg)).into());
+    }
+
+    let keys: Vec<&JWK<Empty>> = jwks.keys.iter().filter(|key| is_signing_key(key)).collect();
+    // If there is more than one key, the token MUST have a key id
+    let key = if keys.len() > 1 {
+        let token_kid = header.registered.key_id.ok_or(Decode::MissingKid)?;
+        let key = keys
+            .iter()
+            .find(|key| key.common.key_id.as_ref() == Some(&token_kid));
+        key.ok_or(Decode::MissingKey(token_kid))?
+    } else if jwks.keys.is_empty() {
+        // TODO We would want to verify the keyset is >1 in the constructor
+        // rather than every decode call, but we can't return an error in new().
+        return Err(Decode::EmptySet.into());
+    } else {
+        keys.first().ok_or(Decode::NoSigningKey)?
+    };
+
+    if let Some(alg) = key.common.algorithm.as_ref() {
+        if let &jwa::Algorithm::Signature(sig) = alg {
+            if header.registered.algorithm != sig {
+                return wrong_key!(sig, header.registered.algorithm);
+            }
+        } else {
+            return wrong_key!(SignatureAlgorithm::default(), alg);
+        }
+    }
+
+    match key.algorithm {
+        // HMAC
+        AlgorithmParameters::OctetKey { ref value, .. } => match alg {
+            SignatureAlgorithm::HS256 | SignatureAlgorithm::HS384 | SignatureAlgorithm::HS512 => {
+                *token = token.decode(&Secret::Bytes(value.clone()), alg)?;
+                Ok(())
+            }
+            _ => wrong_key!("HS256 | HS384 | HS512", alg),
+        },
+        AlgorithmParameters::RSA(ref params) => match alg {
+            SignatureAlgorithm::RS256
+            | SignatureAlgorithm::RS384
+            | SignatureAlgorithm::RS512
+            | SignatureAlgorithm::PS256
+            | SignatureAlgorithm::PS384
+            | SignatureAlgorithm::PS512 => {
+                let pkcs = Secret::RSAModulusExponent {
+                    n: params.n.clone(),
+                    e: params.e.clone(),
+                };
+                *token = token.decode(&pkcs, alg)?;
+                Ok(())
+            }
+            _ => wrong_key!("RS256 | RS384 | RS512 | PS256 | PS384 | PS512", alg),
+        },
+        AlgorithmParameters::EllipticCurve(ref params) => {
+            let expected = match params.curve {
+                EllipticCurve::P256 => SignatureAlgorithm::ES256,
+                EllipticCurve::P384 => SignatureAlgorithm::ES384,
+                // ring cannot verify P-521 signatures
+                EllipticCurve::P521 => {
+                    return Err(Decode::UnsupportedKey(String::from("P-521")).into())
+                }
+            };
+            if alg != expected {
+                return wrong_key!(expected, alg);
+            }
+            // Uncompressed point, as ring expects it
+            let mut point = vec![0x04];
+            point.extend_from_slice(&params.x);
+            point.extend_from_slice(&params.y);
+            *token = token.decode(&Secret::PublicKey(point), alg)?;
+            Ok(())
+        }
+    }
+}
+
 impl<C: CompactJson + Claims> Client<Discovered, C> {
     /// Constructs a client from an issuer url and client parameters via discovery
+    #[cfg(feature = "reqwest")]
     pub async fn discover(
         id: String,
         secret: String,
         redirect: Option<String>,
         issuer: Url,
+    ) -> Result<Self, Error> {
+        Self::discover_with_options(id, secret, redirect, issuer, &DiscoveryOptions::default())
+            .await
+    }
+
+    /// Constructs a client from an issuer url and client parameters via discovery, using the
+    /// given discovery options.
+    #[cfg(feature = "reqwest")]
+    pub async fn discover_with_options(
+        id: String,
+        secret: String,
+        redirect: Option<String>,
+        issuer: Url,
+        options: &DiscoveryOptions,
     ) -> Result<Self, Error> {
         let http_client = reqwest::Client::new();
-        let config = discovered::discover(&http_client, issuer).await?;
-        let jwks = discovered::jwks(&http_client, config.jwks_uri.clone()).await?;
+        Self::discover_with_client(id, secret, redirect, issuer, http_client, options).await
+    }
+
+    /// Constructs a client from an issuer url and client parameters via discovery, sending all
+    /// requests with the given HTTP client.
+    pub async fn discover_with_client(
+        id: String,
+        secret: String,
+        redirect: Option<String>,
+        issuer: Url,
+        http_client: impl HttpClient + 'static,
+        options: &DiscoveryOptions,
+    ) -> Result<Self, Error> {
+        let (config, mut cache) = discovered::discover(&http_client, issuer, options).await?;
+        let (jwks, jwks_cache) = discovered::jwks(&http_client, config.jwks_uri.clone()).await?;
+        cache.jwks = jwks_cache;
         let provider = Discovered(config);
-        Ok(Self::new(
-            provider,
+        let mut client = Self::new(provider, id, secret, redirect, http_client, Some(jwks));
+        client.cache = Some(cache);
+        Ok(client)
+    }
+
+    /// Constructs a client from a previously exported metadata snapshot, without any network
+    /// requests. The snapshot is considered stale, so the first `refresh` fetches it again from
+    /// the config issuer, with the discovery mode and options of the snapshot.
+    pub fn from_metadata(
+        id: String,
+        secret: String,
+        redirect: Option<String>,
+        http_client: impl HttpClient + 'static,
+        metadata: Metadata,
+    ) -> Result<Self, Error> {
+        let cache = MetadataCache::offline(&metadata.config, metadata.mode, &metadata.options)?;
+        let mut client = Self::new(
+            Discovered(metadata.config),
             id,
             secret,
             redirect,
             http_client,
-            Some(jwks),
-        ))
+            Some(metadata.jwks),
+        );
+        client.cache = Some(cache);
+        Ok(client)
+    }
+
+    /// A snapshot of the current provider config and key set to persist for `from_metadata`.
+    /// None if the client has no key set.
+    pub fn metadata(&self) -> Option<Metadata> {
+        let (mode, options) = match self.cache {
+            Some(ref cache) => (cache.mode, cache.options.clone()),
+            None => Default::default(),
+        };
+        self.jwks.as_ref().map(|jwks| Metadata {
+            config: self.config().clone(),
+            jwks: JWKSet {
+                keys: jwks.keys.clone(),
+            },
+            mode,
+            options,
+        })
+    }
+
+    /// Refreshes the discovered config and key set once their HTTP cache lifetime is over, using
+    /// conditional requests, so endpoint and key rotations are picked up without rebuilding the
+    /// client. Returns true if anything changed. Does nothing for clients not built via discovery.
+    /// To refresh a shared client in the background, see `refresh_periodically`.
+    pub async fn refresh(&mut self) -> Result<bool, Error> {
+        match self.cache {
+            Some(ref mut cache) => {
+                discovered::refresh(
+                    &*self.http_client,
+                    cache,
+                    &mut self.provider.0,
+                    &mut self.jwks,
+                )
+                .await
+            }
+            None => Ok(false),
+        }
     }
+
+    /// Keeps the discovered config and key set of a shared client fresh in the background. Waits
+    /// until the earliest of them expires, or `interval` if the provider sent no future expiry,
+    /// then refreshes them like `refresh` with the given HTTP client. The lock is only held to
+    /// copy the metadata out and to store the refreshed metadata, never during requests.
+    ///
+    /// Returns once the task holds the last reference to the client, or with the first refresh
+    /// error. Spawn it on your runtime with its sleep function, e.g.
+    ///
+    /// ```rust,ignore
+    /// let client = Arc::new(RwLock::new(client));
+    /// tokio::spawn(Client::refresh_periodically(
+    ///     client.clone(),
+    ///     reqwest::Client::new(),
+    ///     Duration::from_secs(3600),
+    ///     tokio::time::delay_for,
+    /// ));
+    /// ```
+    pub async fn refresh_periodically<S, F>(
+        client: Arc<RwLock<Self>>,
+        http_client: impl HttpClient,
+        interval: StdDuration,
+        sleep: S,
+    ) -> Result<(), Error>
+    where
+        S: Fn(StdDuration) -> F,
+        F: Future<Output = ()>,
+    {
+        loop {
+            let expires = client
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .expires();
+            let wait = expires
+                .and_then(|expires| (expires - Utc::now()).to_std().ok())
+                .filter(|wait| *wait > StdDuration::from_secs(0))
+                .unwrap_or(interval);
+            sleep(wait).await;
+            if Arc::strong_count(&client) == 1 {
+                return Ok(());
+            }
+
+            let snapshot = {
+                let client = client.read().unwrap_or_else(PoisonError::into_inner);
+                client.cache.clone().map(|cache| {
+                    let jwks = client.jwks.as_ref().map(|jwks| JWKSet {
+                        keys: jwks.keys.clone(),
+                    });
+                    (cache, client.config().clone(), jwks)
+                })
+            };
+            let (mut cache, mut config, mut jwks) = match snapshot {
+                Some(snapshot) => snapshot,
+                None => return Ok(()),
+            };
+            let changed =
+                discovered::refresh(&http_client, &mut cache, &mut config, &mut jwks).await?;
+
+            let mut client = client.write().unwrap_or_else(PoisonError::into_inner);
+            client.cache = Some(cache);
+            if changed {
+                client.provider.0 = config;
+                client.jwks = jwks;
+            }
+        }
+    }
+
+    /// When the discovered config or key set becomes stale and should be refreshed, the earliest
+    /// expiry the provider sent. None if it did not send any.
+    pub fn expires(&self) -> Option<DateTime<Utc>> {
+        self.cache.as_ref().and_then(MetadataCache::expires)
+    }
+
     /// Passthrough to the redirect_url stored in inth_oauth2 as a str.
     pub fn redirect_url(&self) -> &str {
         self.redirect_uri
@@ -84,9 +387,19 @@ impl<C: CompactJson + Claims> Client<Discovered, C> {
         &self.provider.0
     }
 
+    /// The mutual TLS endpoint aliases of the provider, if the client authenticates with mTLS.
+    fn mtls_endpoint_aliases(&self) -> Option<&MtlsEndpointAliases> {
+        if self.auth_method.is_mtls() {
+            self.config().mtls_endpoint_aliases.as_ref()
+        } else {
+            None
+        }
+    }
+
     /// Constructs the auth_url to redirect a client to the provider. Options are... optional. Use
     /// them as needed. Keep the Options struct around for authentication, or at least the nonce
     /// and max_age parameter - we need to verify they stay the same and validate if you used them.
+    /// Call `Options::validate` first if you want to check them against the provider config.
     pub fn auth_url(&self, options: &Options) -> Url {
         let scope = match options.scope {
             Some(ref scope) => {
@@ -135,22 +448,74 @@ impl<C: CompactJson + Claims> Client<Discovered, C> {
             if let Some(ref acr_values) = options.acr_values {
                 query.append_pair("acr_values", acr_values.as_str());
             }
+            if let Some(ref response_mode) = options.response_mode {
+                query.append_pair("response_mode", response_mode.as_str());
+            }
+            for resource in &options.resource {
+                query.append_pair("resource", resource);
+            }
+            if let Some(ref details) = options.authorization_details {
+                // Serializing plain strings and maps cannot fail
+                let details = serde_json::to_string(details).unwrap_or_default();
+                query.append_pair("authorization_details", &details);
+            }
+            if let Some(ref claims) = options.claims {
+                let claims = serde_json::to_string(claims).unwrap_or_default();
+                query.append_pair("claims", &claims);
+            }
         }
         url
     }
 
-    /// Given an auth_code and auth options, request the token, decode, and validate it.
+    /// Given an auth_code and validation options, request the token, decode, and validate it.
+    /// Without options the client's `validation` is used.
     pub async fn authenticate(
         &self,
         auth_code: &str,
-        nonce: Option<&str>,
-        max_age: Option<&Duration>,
+        options: Option<&ValidationOptions>,
     ) -> Result<Token<C>, Error> {
         let bearer = self.request_token(auth_code).await.map_err(Error::from)?;
+        self.verify_bearer(bearer, options)
+    }
+
+    /// Requests a token with the resource owner's username and password, decoding and
+    /// validating the ID token if there is one. The grant is deprecated, only use it for legacy
+    /// setups that cannot redirect to the provider. Needs the `password-grant` feature.
+    ///
+    /// See [RFC 6749, section 4.3](http://tools.ietf.org/html/rfc6749#section-4.3).
+    #[cfg(feature = "password-grant")]
+    pub async fn request_token_password(
+        &self,
+        username: &str,
+        password: &str,
+        scope: Option<&str>,
+    ) -> Result<Token<C>, Error> {
+        let body = {
+            let mut body = Serializer::new(String::new());
+            body.append_pair("grant_type", GrantType::Password.as_str());
+            body.append_pair("username", username);
+            body.append_pair("password", password);
+            if let Some(scope) = scope {
+                body.append_pair("scope", scope);
+            }
+            body.finish()
+        };
+
+        let json = self.post_token(body).await?;
+        let bearer: Bearer = serde_json::from_value(json).map_err(ClientError::from)?;
+        self.verify_bearer(bearer, None)
+    }
+
+    /// Decodes and validates the ID token of a token response, if there is one.
+    fn verify_bearer(
+        &self,
+        bearer: Bearer,
+        options: Option<&ValidationOptions>,
+    ) -> Result<Token<C>, Error> {
         let mut token: Token<C> = bearer.into();
         if let Some(mut id_token) = token.id_token.as_mut() {
-            self.decode_token(&mut id_token)?;
-            self.validate_token(&id_token, nonce, max_age)?;
+            self.decode_jws(&mut id_token, options.unwrap_or(&self.validation))?;
+            self.validate_token(&id_token, options)?;
         }
         Ok(token)
     }
@@ -160,99 +525,124 @@ impl<C: CompactJson + Claims> Client<Discovered, C> {
     /// - Decode::MissingKid if the keyset has multiple keys but the key id on the token is missing
     /// - Decode::MissingKey if the given key id is not in the key set
     /// - Decode::EmptySet if the keyset is empty
+    /// - Decode::NoSigningKey if the use or key_ops of the keys rule out signatures
+    /// - Decode::UnsupportedKey if the key is on a curve that cannot be verified
+    /// - Decode::Unsigned if the token alg is none
+    /// - Decode::DisallowedAlgorithm if the token alg is not allowed. Unless the `validation`
+    ///   of the client lists algorithms, the allowed ones are those of
+    ///   `id_token_signing_alg_values_supported` that are asymmetric
     /// - Jose::WrongKeyType if the alg of the key and the alg in the token header mismatch
     /// - Jose::WrongKeyType if the specified key alg isn't a signature algorithm
     /// - Jose error if decoding fails
     pub fn decode_token(&self, token: &mut IdToken<C>) -> Result<(), Error> {
+        self.decode_jws(token, &self.validation)
+    }
+
+    /// Checks a JWT access token is bound to the given DER encoded client certificate, the one
+    /// presented in the mutual TLS connection the token was used on. Opaque tokens can be
+    /// checked with `Introspection::verify_certificate` instead. Errors are the ones of
+    /// `decode_token` and `Confirmation::verify_certificate`.
+    ///
+    /// See [RFC 8705, section 3](https://tools.ietf.org/html/rfc8705#section-3).
+    pub fn verify_certificate_bound_token(
+        &self,
+        access_token: &str,
+        certificate: &[u8],
+    ) -> Result<(), Error> {
+        let mut token: Compact<ConfirmationClaims, Empty> = Compact::new_encoded(access_token);
+        self.decode_jws(&mut token, &self.validation)?;
+        let claims = token.payload()?;
+        confirmation::verify_certificate(claims.cnf.as_ref(), certificate)
+    }
+
+    fn decode_jws<T: CompactJson>(
+        &self,
+        token: &mut Compact<T, Empty>,
+        options: &ValidationOptions,
+    ) -> Result<(), Error> {
         // This is an early return if the token is already decoded
         if let Compact::Decoded { .. } = *token {
             return Ok(());
         }
 
-        if self.jwks.is_none() {
-            return Ok(());
-        }
-
-        let jwks = self.jwks.as_ref().unwrap();
-
-        let header = token.unverified_header()?;
-        // If there is more than one key, the token MUST have a key id
-        let key = if jwks.keys.len() > 1 {
-            let token_kid = header.registered.key_id.ok_or(Decode::MissingKid)?;
-            jwks.find(&token_kid).ok_or(Decode::MissingKey(token_kid))?
-        } else {
-            // TODO We would want to verify the keyset is >1 in the constructor
-            // rather than every decode call, but we can't return an error in new().
-            jwks.keys.first().as_ref().ok_or(Decode::EmptySet)?
-        };
-
-        if let Some(alg) = key.common.algorithm.as_ref() {
-            if let &jwa::Algorithm::Signature(sig) = alg {
-                if header.registered.algorithm != sig {
-                    return wrong_key!(sig, header.registered.algorithm);
-                }
-            } else {
-                return wrong_key!(SignatureAlgorithm::default(), alg);
+        match self.jwks {
+            Some(ref jwks) => {
+                let algorithms = self.allowed_algorithms(options);
+                decode_jws(jwks, token, algorithms.as_deref())
             }
+            None => Ok(()),
         }
+    }
 
-        let alg = header.registered.algorithm;
-        match key.algorithm {
-            // HMAC
-            AlgorithmParameters::OctetKey { ref value, .. } => match alg {
-                SignatureAlgorithm::HS256
-                | SignatureAlgorithm::HS384
-                | SignatureAlgorithm::HS512 => {
-                    *token = token.decode(&Secret::Bytes(value.clone()), alg)?;
-                    Ok(())
-                }
-                _ => wrong_key!("HS256 | HS384 | HS512", alg),
-            },
-            AlgorithmParameters::RSA(ref params) => match alg {
-                SignatureAlgorithm::RS256
-                | SignatureAlgorithm::RS384
-                | SignatureAlgorithm::RS512 => {
-                    let pkcs = Secret::RSAModulusExponent {
-                        n: params.n.clone(),
-                        e: params.e.clone(),
-                    };
-                    *token = token.decode(&pkcs, alg)?;
-                    Ok(())
-                }
-                _ => wrong_key!("RS256 | RS384 | RS512", alg),
-            },
-            AlgorithmParameters::EllipticCurve(_) => unimplemented!("No support for EC keys yet"),
+    /// The algorithms of the options, or else the advertised ones that are safe. None if the
+    /// provider advertises none, e.g. an RFC 8414 server.
+    fn allowed_algorithms(&self, options: &ValidationOptions) -> Option<Vec<SignatureAlgorithm>> {
+        if !options.algorithms.is_empty() {
+            return Some(options.algorithms.clone());
+        }
+        let supported = &self.config().id_token_signing_alg_values_supported;
+        if supported.is_empty() {
+            return None;
         }
+        let algorithms = supported
+            .iter()
+            .filter_map(|alg| serde_json::from_value(Value::String(alg.clone())).ok())
+            .filter(|alg| SAFE_ALGORITHMS.contains(alg))
+            .collect();
+        Some(algorithms)
     }
 
-    /// Validate a decoded token. If you don't get an error, its valid! Nonce and max_age come from
-    /// your auth_uri options. Errors are:
+    /// Validate a decoded token. If you don't get an error, its valid! Without options the
+    /// client's `validation` is used; the nonce and max_age come from your auth_uri options.
+    /// Errors are:
     ///
     /// - Jose Error if the Token isn't decoded
-    /// - Validation::Mismatch::Issuer if the provider issuer and token issuer mismatch
+    /// - Validation::Mismatch::Algorithm if the token alg is not in the allowed algorithms
+    /// - Validation::Mismatch::Issuer if the token issuer is not the provider or an allowed issuer
     /// - Validation::Mismatch::Nonce if a given nonce and the token nonce mismatch
     /// - Validation::Missing::Nonce if either the token or args has a nonce and the other does not
     /// - Validation::Missing::Audience if the token aud doesn't contain the client id
+    /// - Validation::Mismatch::Audience if trusted audiences are set and the token has another one
     /// - Validation::Missing::AuthorizedParty if there are multiple audiences and azp is missing
     /// - Validation::Mismatch::AuthorizedParty if the azp is not the client_id
     /// - Validation::Expired::Expires if the current time is past the expiration time
     /// - Validation::Expired::MaxAge is the token is older than the provided max_age
     /// - Validation::Missing::Authtime if a max_age was given and the token has no auth time
+    /// - Validation::Missing::Claim if a required claim is missing
+    /// - Validation::InsufficientAuthentication if the acr or amr are not the required ones
     pub fn validate_token(
         &self,
         token: &IdToken<C>,
-        nonce: Option<&str>,
-        max_age: Option<&Duration>,
+        options: Option<&ValidationOptions>,
     ) -> Result<(), Error> {
+        let options = options.unwrap_or(&self.validation);
         let claims = token.payload()?;
 
-        if claims.iss() != &self.config().issuer {
-            let expected = self.config().issuer.as_str().to_string();
+        if !options.algorithms.is_empty() {
+            let alg = token.header()?.registered.algorithm;
+            if !options.algorithms.contains(&alg) {
+                let expected = format!("{:?}", options.algorithms);
+                let actual = format!("{:?}", alg);
+                return Err(Validation::Mismatch(Mismatch::Algorithm { expected, actual }).into());
+            }
+        }
+
+        let issuers = if options.issuers.is_empty() {
+            std::slice::from_ref(&self.config().issuer)
+        } else {
+            options.issuers.as_slice()
+        };
+        if !issuers.contains(claims.iss()) {
+            let expected = issuers
+                .iter()
+                .map(Url::as_str)
+                .collect::<Vec<_>>()
+                .join(" ");
             let actual = claims.iss().as_str().to_string();
             return Err(Validation::Mismatch(Mismatch::Issuer { expected, actual }).into());
         }
 
-        match nonce {
+        match options.nonce.as_deref() {
             Some(expected) => match claims.nonce() {
                 Some(actual) => {
                     if expected != actual {
@@ -275,6 +665,17 @@ impl<C: CompactJson + Claims> Client<Discovered, C> {
         if !claims.aud().contains(&self.client_id) {
             return Err(Validation::Missing(Missing::Audience).into());
         }
+        if !options.audiences.is_empty() {
+            let untrusted = claims
+                .aud()
+                .iter()
+                .find(|aud| **aud != self.client_id && !options.audiences.contains(aud));
+            if let Some(actual) = untrusted {
+                let expected = options.audiences.join(" ");
+                let actual = actual.clone();
+                return Err(Validation::Mismatch(Mismatch::Audience { expected, actual }).into());
+            }
+        }
         // By spec, if there are multiple auds, we must have an azp
         if let SingleOrMultiple::Multiple(_) = claims.aud() {
             if let None = claims.azp() {
@@ -297,18 +698,18 @@ impl<C: CompactJson + Claims> Client<Discovered, C> {
         if now.timestamp() < 1504758600 {
             panic!("chrono::Utc::now() can never be before this was written!")
         }
-        if claims.exp() <= now.timestamp() {
+        if claims.exp() <= (now - options.leeway).timestamp() {
             return Err(Validation::Expired(Expiry::Expires(
                 chrono::naive::NaiveDateTime::from_timestamp(claims.exp(), 0),
             ))
             .into());
         }
 
-        if let Some(max) = max_age {
+        if let Some(max) = options.max_age {
             match claims.auth_time() {
                 Some(time) => {
                     let age = chrono::Duration::seconds(now.timestamp() - time);
-                    if age >= *max {
+                    if age >= max + options.leeway {
                         return Err(Validation::Expired(Expiry::MaxAge(age)).into());
                     }
                 }
@@ -316,7 +717,16 @@ impl<C: CompactJson + Claims> Client<Discovered, C> {
             }
         }
 
-        Ok(())
+        if !options.required_claims.is_empty() {
+            let json = serde_json::to_value(claims)?;
+            for name in &options.required_claims {
+                if json.get(name).unwrap_or(&Value::Null).is_null() {
+                    return Err(Validation::Missing(Missing::Claim(name.clone())).into());
+                }
+            }
+        }
+
+        options.authentication.verify(claims)
     }
 
     /// Get a userinfo json document for a given token at the provider's userinfo endpoint.
@@ -329,17 +739,16 @@ impl<C: CompactJson + Claims> Client<Discovered, C> {
     /// - Error::Json if the response is not a valid Userinfo document
     /// - Userinfo::MismatchSubject if the returned userinfo document and tokens subject mismatch
     pub async fn request_userinfo(&self, token: &Token<C>) -> Result<Userinfo, Error> {
-        match self.config().userinfo_endpoint {
-            Some(ref url) => {
+        let aliases = self.mtls_endpoint_aliases();
+        let url = aliases
+            .and_then(|aliases| aliases.userinfo_endpoint.as_ref())
+            .or_else(|| self.config().userinfo_endpoint.as_ref());
+        match url {
+            Some(url) => {
                 let claims = token.id_token.as_ref().map(|x| x.payload()).transpose()?;
-                let auth_code = token.bearer.access_token.to_string();
-                let resp = self
-                    .http_client
-                    .get(url.clone())
-                    .bearer_auth(auth_code)
-                    .send()
-                    .await?;
-                let info: Userinfo = resp.json().await?;
+                let request = HttpRequest::get(url.clone());
+                let resp = self.send_with_token(request, &token.bearer).await?;
+                let info: Userinfo = resp.error_for_status()?.json()?;
                 if let Some(claims) = claims {
                     if let Some(info_sub) = &info.sub {
                         if claims.sub() != info_sub {
@@ -354,6 +763,194 @@ impl<C: CompactJson + Claims> Client<Discovered, C> {
             None => Err(ErrorUserinfo::NoUrl.into()),
         }
     }
+
+    /// Resolves the aggregated and distributed claims of a userinfo response or ID token with the
+    /// client's HTTP client. See [`ClaimsResolver::resolve`](struct.ClaimsResolver.html#method.resolve).
+    pub async fn resolve_claims<T: Serialize>(
+        &self,
+        resolver: &ClaimsResolver,
+        claims: &T,
+    ) -> Result<Map<String, Value>, Error> {
+        resolver.resolve(&*self.http_client, claims).await
+    }
+
+    /// Ask the provider's introspection endpoint about a token. Errors are:
+    ///
+    /// - Introspection::NoUrl if this provider doesn't have an introspection endpoint
+    /// - Error::ClientError if the request fails or the provider returns an error
+    ///
+    /// See [RFC 7662](https://tools.ietf.org/html/rfc7662).
+    pub async fn introspect(
+        &self,
+        token: &str,
+        token_type_hint: Option<&str>,
+    ) -> Result<Introspection, Error> {
+        let aliases = self.mtls_endpoint_aliases();
+        let url = aliases
+            .and_then(|aliases| aliases.introspection_endpoint.as_ref())
+            .or_else(|| self.config().token_introspection_endpoint.as_ref());
+        let url = match url {
+            Some(url) => url.clone(),
+            None => return Err(ErrorIntrospection::NoUrl.into()),
+        };
+        let body = {
+            let mut body = Serializer::new(String::new());
+            body.append_pair("token", token);
+            if let Some(token_type_hint) = token_type_hint {
+                body.append_pair("token_type_hint", token_type_hint);
+            }
+            body.finish()
+        };
+
+        let json = self
+            .post_form(url, body, false, Some(self.auth_method))
+            .await?;
+        let introspection = serde_json::from_value(json).map_err(ClientError::from)?;
+        Ok(introspection)
+    }
+
+    /// Asks the provider to authenticate a user on their own device. Get the tokens with
+    /// `backchannel_token` or `backchannel_push`, depending on the delivery mode. Errors are:
+    ///
+    /// - Backchannel::NoUrl if this provider doesn't have a backchannel authentication endpoint
+    /// - Error::ClientError if the request fails or the provider returns an error
+    ///
+    /// See [CIBA, section 7](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.7).
+    pub async fn backchannel_authenticate(
+        &self,
+        request: &BackchannelAuthentication,
+    ) -> Result<BackchannelAuthenticationResponse, Error> {
+        let aliases = self.mtls_endpoint_aliases();
+        let url = aliases
+            .and_then(|aliases| aliases.backchannel_authentication_endpoint.as_ref())
+            .or_else(|| self.config().backchannel_authentication_endpoint.as_ref());
+        let url = match url {
+            Some(url) => url.clone(),
+            None => return Err(Backchannel::NoUrl.into()),
+        };
+        let body = {
+            let mut body = Serializer::new(String::new());
+            let scope = match request.scope {
+                Some(ref scope) if scope.split_whitespace().any(|scope| scope == "openid") => {
+                    scope.clone()
+                }
+                Some(ref scope) => String::from("openid ") + scope,
+                None => String::from("openid"),
+            };
+            body.append_pair("scope", &scope);
+            let params = [
+                ("acr_values", &request.acr_values),
+                ("login_hint_token", &request.login_hint_token),
+                ("id_token_hint", &request.id_token_hint),
+                ("login_hint", &request.login_hint),
+                ("binding_message", &request.binding_message),
+                ("user_code", &request.user_code),
+                (
+                    "client_notification_token",
+                    &request.client_notification_token,
+                ),
+            ];
+            for (name, value) in params.iter() {
+                if let Some(value) = value {
+                    body.append_pair(name, value);
+                }
+            }
+            if let Some(requested_expiry) = request.requested_expiry {
+                body.append_pair("requested_expiry", &requested_expiry.to_string());
+            }
+            body.finish()
+        };
+
+        let json = self
+            .post_form(url, body, false, Some(self.auth_method))
+            .await?;
+        let response = serde_json::from_value(json).map_err(ClientError::from)?;
+        Ok(response)
+    }
+
+    /// Asks the token endpoint for the tokens of a backchannel authentication, in the poll and
+    /// ping modes. Returns None while the user has not answered yet; poll again after
+    /// `poll_interval`, which grows when the provider asks to slow down.
+    ///
+    /// See [CIBA, section 10.1](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.10.1).
+    pub async fn backchannel_token(
+        &self,
+        response: &mut BackchannelAuthenticationResponse,
+    ) -> Result<Option<Token<C>>, Error> {
+        let body = {
+            let mut body = Serializer::new(String::new());
+            body.append_pair("grant_type", GrantType::Ciba.as_str());
+            body.append_pair("auth_req_id", &response.auth_req_id);
+            body.finish()
+        };
+
+        let json = match self.post_token(body).await {
+            Ok(json) => json,
+            Err(ClientError::OAuth2(ref error)) if is_error(error, "authorization_pending") => {
+                return Ok(None)
+            }
+            Err(ClientError::OAuth2(ref error)) if is_error(error, "slow_down") => {
+                response.interval = Some(response.interval.unwrap_or(5) + 5);
+                return Ok(None);
+            }
+            Err(error) => return Err(error.into()),
+        };
+        let bearer: Bearer = serde_json::from_value(json).map_err(ClientError::from)?;
+        self.verify_bearer(bearer, None).map(Some)
+    }
+
+    /// Validates the tokens the provider pushed to the client notification endpoint, in push
+    /// mode. Errors are:
+    ///
+    /// - Backchannel::NoToken if the notification carries no tokens
+    /// - Backchannel::NoIdToken if the tokens have no ID token
+    /// - Errors of `decode_token` and `validate_token` for the ID token
+    /// - Backchannel::AuthReqIdMismatch if the ID token is for another request
+    /// - Backchannel::TokenHashMismatch if the `at_hash` or `rt_hash` of the ID token is
+    ///   missing or not the hash of the pushed access or refresh token
+    ///
+    /// See [CIBA, section 10.3](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.10.3).
+    pub fn backchannel_push(
+        &self,
+        notification: BackchannelNotification,
+    ) -> Result<Token<C>, Error> {
+        let bearer = notification.bearer.ok_or(Backchannel::NoToken)?;
+        let id_token = bearer.id_token.clone().ok_or(Backchannel::NoIdToken)?;
+        let token = self.verify_bearer(bearer, None)?;
+
+        let claims = backchannel::push_claims(&id_token);
+        let actual = claims.auth_req_id.unwrap_or_default();
+        if actual != notification.auth_req_id {
+            let expected = notification.auth_req_id;
+            return Err(Backchannel::AuthReqIdMismatch { expected, actual }.into());
+        }
+        let algorithm = match token.id_token {
+            Some(ref id_token) => id_token.header()?.registered.algorithm,
+            None => return Err(Backchannel::NoIdToken.into()),
+        };
+        let access_token = Some(token.bearer.access_token.as_str());
+        let hashes = [
+            ("at_hash", claims.at_hash, access_token),
+            (
+                "rt_hash",
+                claims.rt_hash,
+                token.bearer.refresh_token.as_deref(),
+            ),
+        ];
+        for (name, hash, pushed) in hashes.iter() {
+            if let Some(pushed) = pushed {
+                let expected = backchannel::token_hash(algorithm, pushed);
+                if expected.is_none() || hash.as_ref() != expected.as_ref() {
+                    return Err(Backchannel::TokenHashMismatch(name).into());
+                }
+            }
+        }
+        Ok(token)
+    }
+}
+
+fn is_error(error: &OAuth2Error, code: &str) -> bool {
+    error.error == OAuth2ErrorCode::from(code)
 }
 
 impl<P, C> Client<P, C>
@@ -366,6 +963,7 @@ where
     /// # Examples
     ///
     /// ```
+    /// # #[cfg(feature = "reqwest")] {
     /// use openid::{Client, StandardClaims};
     /// use openid::provider::google::Installed;
     ///
@@ -376,13 +974,14 @@ where
     ///     Some(String::from("urn:ietf:wg:oauth:2.0:oob")),
     ///     reqwest::Client::new(), None,
     /// );
+    /// # }
     /// ```
     pub fn new(
         provider: P,
         client_id: String,
         client_secret: String,
         redirect_uri: Option<String>,
-        http_client: reqwest::Client,
+        http_client: impl HttpClient + 'static,
         jwks: Option<JWKSet<Empty>>,
     ) -> Self {
         Client {
@@ -390,8 +989,13 @@ where
             client_id,
             client_secret,
             redirect_uri,
-            http_client,
+            http_client: Box::new(http_client),
+            auth_method: ClientAuthMethod::default(),
+            dpop: None,
+            dpop_nonces: Mutex::new(HashMap::new()),
             jwks,
+            validation: ValidationOptions::default(),
+            cache: None,
             marker: PhantomData,
         }
     }
@@ -403,6 +1007,7 @@ where
     /// # Examples
     ///
     /// ```
+    /// # #[cfg(feature = "reqwest")] {
     /// use openid::Client;
     /// use openid::provider::google::Installed;
     ///
@@ -418,6 +1023,7 @@ where
     ///     Some("https://www.googleapis.com/auth/userinfo.email"),
     ///     None,
     /// );
+    /// # }
     /// ```
     pub fn auth_uri(&self, scope: Option<&str>, state: Option<&str>) -> Url {
         let mut uri = self.provider.auth_uri().clone();
@@ -443,17 +1049,154 @@ where
     }
 
     async fn post_token(&self, body: String) -> Result<Value, ClientError> {
-        let json = self
-            .http_client
-            .post(self.provider.token_uri().clone())
-            .basic_auth(&self.client_id, Some(&self.client_secret))
-            .header(ACCEPT, "application/json")
-            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
-            .body(body)
-            .send()
-            .await?
-            .json::<Value>()
-            .await?;
+        self.post_token_as(body, Some(self.auth_method)).await
+    }
+
+    /// Posts to the token endpoint authenticating the client with the method, not at all if
+    /// None.
+    async fn post_token_as(
+        &self,
+        body: String,
+        auth_method: Option<ClientAuthMethod>,
+    ) -> Result<Value, ClientError> {
+        let url = match self.provider.mtls_token_uri() {
+            Some(url) if auth_method.is_some_and(|method| method.is_mtls()) => url,
+            _ => self.provider.token_uri(),
+        };
+        self.post_form(url.clone(), body, true, auth_method).await
+    }
+
+    /// Sends a request to a protected resource with the access token, in the `DPoP` scheme with
+    /// a proof if the token is DPoP bound, otherwise as a bearer token. Errors with
+    /// Validation::Missing::DPoPKey for a DPoP bound token if the client has no DPoP key.
+    ///
+    /// See [RFC 9449, section 7](https://tools.ietf.org/html/rfc9449#section-7).
+    pub async fn send_with_token(
+        &self,
+        request: HttpRequest,
+        token: &Bearer,
+    ) -> Result<HttpResponse, Error> {
+        let access_token = token.access_token.as_str();
+        if token.is_dpop() {
+            if self.dpop.is_none() {
+                return Err(Validation::Missing(Missing::DPoPKey).into());
+            }
+            let request = request.header(AUTHORIZATION, &format!("DPoP {}", access_token))?;
+            self.send_dpop(request, Some(access_token)).await
+        } else {
+            let request = request.bearer_auth(access_token)?;
+            Ok(self.http_client.send(request).await?)
+        }
+    }
+
+    /// Creates a DPoP proof for a request, with the last nonce the server sent. None if the
+    /// client has no DPoP key.
+    pub fn dpop_proof(
+        &self,
+        method: &Method,
+        url: &Url,
+        access_token: Option<&str>,
+    ) -> Result<Option<String>, Jose> {
+        let key = match self.dpop {
+            Some(ref key) => key,
+            None => return Ok(None),
+        };
+        let origin = url.origin().ascii_serialization();
+        let nonce = self
+            .dpop_nonces
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&origin)
+            .cloned();
+        key.proof(method, url, access_token, nonce.as_deref())
+            .map(Some)
+    }
+
+    /// Sends a request with a DPoP proof if the client has a key. When the server rejects the
+    /// proof and asks for a nonce, retries once with it.
+    ///
+    /// See [RFC 9449, section 8](https://tools.ietf.org/html/rfc9449#section-8).
+    async fn send_dpop<E>(
+        &self,
+        request: HttpRequest,
+        access_token: Option<&str>,
+    ) -> Result<HttpResponse, E>
+    where
+        E: From<Http> + From<Jose>,
+    {
+        let mut retry = self.dpop.is_some();
+        loop {
+            let mut attempt = request.clone();
+            if let Some(proof) = self.dpop_proof(&attempt.method, &attempt.url, access_token)? {
+                let proof = HeaderValue::from_str(&proof).map_err(Http::new)?;
+                attempt.headers.insert(DPOP, proof);
+            }
+            let resp = self.http_client.send(attempt).await?;
+
+            let nonce = resp
+                .headers
+                .get(DPOP_NONCE)
+                .and_then(|nonce| nonce.to_str().ok());
+            if let Some(nonce) = nonce {
+                let origin = request.url.origin().ascii_serialization();
+                let previous = self
+                    .dpop_nonces
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .insert(origin, nonce.to_string());
+                let challenged = resp.status == StatusCode::BAD_REQUEST
+                    || resp.status == StatusCode::UNAUTHORIZED;
+                if retry && challenged && previous.as_deref() != Some(nonce) {
+                    retry = false;
+                    continue;
+                }
+            }
+            return Ok(resp);
+        }
+    }
+
+    /// Posts a form to an endpoint authenticating the client with the method, if any, and turns
+    /// OAuth 2.0 error responses into errors. Token endpoint requests carry a DPoP proof.
+    async fn post_form(
+        &self,
+        url: Url,
+        mut body: String,
+        dpop: bool,
+        auth_method: Option<ClientAuthMethod>,
+    ) -> Result<Value, ClientError> {
+        let mut request = HttpRequest::post(url, Vec::new());
+        {
+            let mut body = Serializer::for_suffix(&mut body, 0);
+            match auth_method {
+                None => {}
+                Some(ClientAuthMethod::ClientSecretBasic) => {
+                    request = request.basic_auth(&self.client_id, &self.client_secret)?;
+                    if self.provider.credentials_in_body() {
+                        body.append_pair("client_id", &self.client_id);
+                        body.append_pair("client_secret", &self.client_secret);
+                    }
+                }
+                Some(ClientAuthMethod::ClientSecretPost) => {
+                    body.append_pair("client_id", &self.client_id);
+                    body.append_pair("client_secret", &self.client_secret);
+                }
+                // The client certificate of the TLS connection authenticates the client
+                Some(ClientAuthMethod::TlsClientAuth)
+                | Some(ClientAuthMethod::SelfSignedTlsClientAuth) => {
+                    body.append_pair("client_id", &self.client_id);
+                }
+            }
+        }
+        request.body = body.into_bytes();
+        let request = request
+            .header(ACCEPT, "application/json")?
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")?;
+        let resp = if dpop {
+            self.send_dpop::<ClientError>(request, None).await?
+        } else {
+            self.http_client.send(request).await?
+        };
+        let json = resp.json::<Value>()?;
 
         let error: Result<OAuth2Error, _> = serde_json::from_value(json.clone());
 
@@ -468,6 +1211,17 @@ where
     ///
     /// See [RFC 6749, section 4.1.3](http://tools.ietf.org/html/rfc6749#section-4.1.3).
     pub async fn request_token(&self, code: &str) -> Result<Bearer, ClientError> {
+        self.request_token_with_resources(code, &[]).await
+    }
+
+    /// Requests an access token using an authorization code, restricted to the given resources.
+    ///
+    /// See [RFC 8707, section 2.2](https://tools.ietf.org/html/rfc8707#section-2.2).
+    pub async fn request_token_with_resources(
+        &self,
+        code: &str,
+        resources: &[&str],
+    ) -> Result<Bearer, ClientError> {
         // Ensure the non thread-safe `Serializer` is not kept across
         // an `await` boundary by localizing it to this inner scope.
         let body = {
@@ -478,11 +1232,10 @@ where
             if let Some(ref redirect_uri) = self.redirect_uri {
                 body.append_pair("redirect_uri", redirect_uri);
             }
-
-            if self.provider.credentials_in_body() {
-                body.append_pair("client_id", &self.client_id);
-                body.append_pair("client_secret", &self.client_secret);
+            for resource in resources {
+                body.append_pair("resource", resource);
             }
+
             body.finish()
         };
 
@@ -498,6 +1251,18 @@ where
         &self,
         token: Bearer,
         scope: Option<&str>,
+    ) -> Result<Bearer, ClientError> {
+        self.refresh_token_with_resources(token, scope, &[]).await
+    }
+
+    /// Refreshes an access token, restricted to the given resources.
+    ///
+    /// See [RFC 8707, section 2.2](https://tools.ietf.org/html/rfc8707#section-2.2).
+    pub async fn refresh_token_with_resources(
+        &self,
+        token: Bearer,
+        scope: Option<&str>,
+        resources: &[&str],
     ) -> Result<Bearer, ClientError> {
         let mut body = Serializer::new(String::new());
         body.append_pair("grant_type", "refresh_token");
@@ -513,10 +1278,8 @@ where
         if let Some(scope) = scope {
             body.append_pair("scope", scope);
         }
-
-        if self.provider.credentials_in_body() {
-            body.append_pair("client_id", &self.client_id);
-            body.append_pair("client_secret", &self.client_secret);
+        for resource in resources {
+            body.append_pair("resource", resource);
         }
         let body = body.finish();
 
@@ -528,6 +1291,76 @@ where
         Ok(new_token)
     }
 
+    /// Requests an access token with a JWT assertion signed by the key, about the subject, e.g.
+    /// the service account itself or a user it acts for. The key is passed per call rather than
+    /// kept on the client, as one client may act for several service accounts, each with its own
+    /// key.
+    ///
+    /// The assertion authenticates the request, so the client only authenticates as well if it
+    /// has a secret or uses mutual TLS. Service accounts without a client secret send no
+    /// `Authorization` header, which providers such as Google would reject.
+    ///
+    /// See [RFC 7523, section 2.1](https://tools.ietf.org/html/rfc7523#section-2.1) and
+    /// [section 3.1](https://tools.ietf.org/html/rfc7523#section-3.1).
+    pub async fn request_token_jwt_bearer(
+        &self,
+        key: &AssertionKey,
+        subject: &str,
+        scope: Option<&str>,
+    ) -> Result<Bearer, ClientError> {
+        let assertion = key.assertion(subject, self.provider.token_uri().as_str())?;
+        let body = {
+            let mut body = Serializer::new(String::new());
+            body.append_pair("grant_type", GrantType::JwtBearer.as_str());
+            body.append_pair("assertion", &assertion);
+            if let Some(scope) = scope {
+                body.append_pair("scope", scope);
+            }
+            body.finish()
+        };
+
+        // Client authentication is optional for assertion grants
+        let auth_method = Some(self.auth_method)
+            .filter(|method| method.is_mtls() || !self.client_secret.is_empty());
+        let json = self.post_token_as(body, auth_method).await?;
+        let token: Bearer = serde_json::from_value(json)?;
+        Ok(token)
+    }
+
+    /// Exchanges a token for another one, e.g. a user's access token for a token to call a
+    /// downstream service on their behalf. The result has the `issued_token_type`.
+    ///
+    /// See [RFC 8693](https://tools.ietf.org/html/rfc8693).
+    pub async fn exchange_token(&self, exchange: &TokenExchange) -> Result<Bearer, ClientError> {
+        let body = {
+            let mut body = Serializer::new(String::new());
+            body.append_pair("grant_type", GrantType::TokenExchange.as_str());
+            body.append_pair("subject_token", &exchange.subject_token);
+            body.append_pair("subject_token_type", exchange.subject_token_type.as_str());
+            if let Some((ref token, ref token_type)) = exchange.actor_token {
+                body.append_pair("actor_token", token);
+                body.append_pair("actor_token_type", token_type.as_str());
+            }
+            for audience in &exchange.audience {
+                body.append_pair("audience", audience);
+            }
+            for resource in &exchange.resource {
+                body.append_pair("resource", resource);
+            }
+            if let Some(ref scope) = exchange.scope {
+                body.append_pair("scope", scope);
+            }
+            if let Some(ref token_type) = exchange.requested_token_type {
+                body.append_pair("requested_token_type", token_type.as_str());
+            }
+            body.finish()
+        };
+
+        let json = self.post_token(body).await?;
+        let token: Bearer = serde_json::from_value(json)?;
+        Ok(token)
+    }
+
     /// Ensures an access token is valid by refreshing it if necessary.
     pub async fn ensure_token(&self, token: Bearer) -> Result<Bearer, ClientError> {
         if token.expired() {
@@ -541,8 +1374,42 @@ where
 #[cfg(test)]
 mod tests {
     use super::Client;
-    use crate::provider::Provider;
-    use url::Url;
+    use crate::{
+        assertion_key::AssertionClaims,
+        backchannel::{BackchannelAuthentication, BackchannelNotification},
+        certificate_thumbprint,
+        confirmation::ConfirmationClaims,
+        dpop::{DPOP, DPOP_NONCE},
+        error::{Backchannel, Decode, Error, Expiry, Jose, Mismatch, Missing, Validation},
+        http_client::{mock::MockHttpClient, HttpRequest, HttpResponse},
+        provider::Provider,
+        AssertionKey, AuthorizationDetail, Bearer, ClaimRequest, ClaimsRequest, ClientAuthMethod,
+        Confirmation, DPoPKey, DiscoveryMode, DiscoveryOptions, Metadata, MtlsEndpointAliases,
+        Options, StandardClaims, Token, TokenExchange, TokenType, ValidationOptions,
+    };
+    use biscuit::{
+        jwa::SignatureAlgorithm,
+        jwk::JWKSet,
+        jws::{Compact, RegisteredHeader, Secret},
+        ClaimsSet, Empty, RegisteredClaims,
+    };
+    use chrono::Utc;
+    use futures::executor::block_on;
+    use http::{
+        header::{HeaderValue, AUTHORIZATION},
+        StatusCode,
+    };
+    use ring::{
+        rand::SystemRandom,
+        signature::{EcdsaKeyPair, KeyPair, RsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING},
+    };
+    use serde_json::json;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex, RwLock},
+        time::Duration,
+    };
+    use url::{form_urlencoded, Url};
 
     struct Test {
         auth_uri: Url,
@@ -565,9 +1432,898 @@ mod tests {
         }
     }
 
+    #[test]
+    fn discover_falls_back_to_oauth_metadata() {
+        let http_client = MockHttpClient::default()
+            .with_json(
+                "https://example.com/.well-known/oauth-authorization-server/tenant",
+                r#"{
+                    "issuer": "https://example.com/tenant",
+                    "authorization_endpoint": "https://example.com/tenant/auth",
+                    "token_endpoint": "https://example.com/tenant/token",
+                    "jwks_uri": "https://example.com/tenant/certs",
+                    "response_types_supported": ["code"]
+                }"#,
+            )
+            .with_json("https://example.com/tenant/certs", r#"{ "keys": [] }"#);
+        let issuer = url::Url::parse("https://example.com/tenant").unwrap();
+        let client: Client = block_on(Client::discover_with_client(
+            String::from("foo"),
+            String::from("bar"),
+            None,
+            issuer,
+            http_client,
+            &DiscoveryOptions::default(),
+        ))
+        .unwrap();
+        assert_eq!(
+            "https://example.com/tenant/token",
+            client.config().token_endpoint.as_str()
+        );
+        assert_eq!(Some(0), client.jwks.as_ref().map(|jwks| jwks.keys.len()));
+    }
+
+    const METADATA: &str = r#"{
+        "config": {
+            "issuer": "https://example.com",
+            "authorization_endpoint": "https://example.com/auth",
+            "token_endpoint": "https://example.com/token",
+            "introspection_endpoint": "https://example.com/introspect",
+            "jwks_uri": "https://example.com/certs",
+            "response_types_supported": ["code"],
+            "subject_types_supported": ["public"],
+            "id_token_signing_alg_values_supported": ["RS256"]
+        },
+        "jwks": {
+            "keys": [{ "kty": "oct", "kid": "key", "k": "c2VjcmV0" }]
+        }
+    }"#;
+
+    fn discovered(http_client: MockHttpClient) -> Client {
+        let metadata: Metadata = serde_json::from_str(METADATA).unwrap();
+        Client::from_metadata(
+            String::from("foo"),
+            String::from("bar"),
+            None,
+            http_client,
+            metadata,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn validation_options() {
+        let now = Utc::now().timestamp();
+        let id_token = |claims: serde_json::Value| {
+            let header = RegisteredHeader {
+                algorithm: SignatureAlgorithm::HS256,
+                key_id: Some(String::from("key")),
+                ..Default::default()
+            };
+            let claims: StandardClaims = serde_json::from_value(claims).unwrap();
+            Compact::new_decoded(From::from(header), claims)
+        };
+        let token = id_token(json!({
+            "iss": "https://example.com/",
+            "sub": "248289761001",
+            "aud": ["foo", "https://api.example.com"],
+            "azp": "foo",
+            "exp": now - 30,
+            "iat": now - 600,
+            "nonce": "n-0S6_WzA2Mj"
+        }));
+        let mut client = discovered(MockHttpClient::default());
+        client.validation = ValidationOptions {
+            nonce: Some(String::from("n-0S6_WzA2Mj")),
+            leeway: chrono::Duration::seconds(60),
+            ..Default::default()
+        };
+        client.validate_token(&token, None).unwrap();
+
+        let fails = |options: ValidationOptions| client.validate_token(&token, Some(&options));
+        match fails(ValidationOptions::default()) {
+            Err(Error::Validation(Validation::Missing(Missing::Nonce))) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        let options = ValidationOptions {
+            audiences: vec![String::from("https://other.example.com")],
+            ..client.validation.clone()
+        };
+        match fails(options) {
+            Err(Error::Validation(Validation::Mismatch(Mismatch::Audience { actual, .. }))) => {
+                assert_eq!("https://api.example.com", actual)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        let options = ValidationOptions {
+            algorithms: vec![SignatureAlgorithm::RS256],
+            ..client.validation.clone()
+        };
+        match fails(options) {
+            Err(Error::Validation(Validation::Mismatch(Mismatch::Algorithm { .. }))) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        let options = ValidationOptions {
+            required_claims: vec![String::from("email")],
+            ..client.validation.clone()
+        };
+        match fails(options) {
+            Err(Error::Validation(Validation::Missing(Missing::Claim(name)))) => {
+                assert_eq!("email", name)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        let options = ValidationOptions {
+            leeway: chrono::Duration::zero(),
+            ..client.validation.clone()
+        };
+        match fails(options) {
+            Err(Error::Validation(Validation::Expired(Expiry::Expires(_)))) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let token = id_token(json!({
+            "iss": "https://other.example.com/",
+            "sub": "248289761001",
+            "aud": "foo",
+            "exp": now + 600,
+            "iat": now
+        }));
+        assert!(client
+            .validate_token(&token, Some(&Default::default()))
+            .is_err());
+        let options = ValidationOptions {
+            issuers: vec![Url::parse("https://other.example.com").unwrap()],
+            ..Default::default()
+        };
+        client.validate_token(&token, Some(&options)).unwrap();
+    }
+
+    /// An RSA and a P-256 key, as a key set and as signing secrets.
+    fn asymmetric_keys() -> (JWKSet<Empty>, Secret, Secret) {
+        let rsa =
+            RsaKeyPair::from_der(include_bytes!("../tests/fixtures/rsa_private_key.der")).unwrap();
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+        let ec =
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref()).unwrap();
+
+        let b64 = |bytes: &[u8]| base64::encode_config(bytes, base64::URL_SAFE_NO_PAD);
+        let point = ec.public_key().as_ref();
+        let jwks = serde_json::from_value(json!({ "keys": [
+            {
+                "kty": "RSA",
+                "kid": "rsa",
+                "n": b64(rsa.public_key().modulus().big_endian_without_leading_zero()),
+                "e": b64(rsa.public_key().exponent().big_endian_without_leading_zero())
+            },
+            { "kty": "EC", "kid": "ec", "crv": "P-256", "x": b64(&point[1..33]), "y": b64(&point[33..]) }
+        ] }))
+        .unwrap();
+        (
+            jwks,
+            Secret::RsaKeyPair(Arc::new(rsa)),
+            Secret::EcdsaKeyPair(Arc::new(ec)),
+        )
+    }
+
+    fn bound_token(algorithm: SignatureAlgorithm, kid: &str, secret: &Secret) -> String {
+        let header = RegisteredHeader {
+            algorithm,
+            key_id: Some(String::from(kid)),
+            ..Default::default()
+        };
+        let claims = ConfirmationClaims {
+            cnf: Some(Confirmation {
+                x5t_s256: Some(certificate_thumbprint(b"abc")),
+            }),
+        };
+        let token = Compact::new_decoded(From::from(header), claims)
+            .encode(secret)
+            .unwrap();
+        token.encoded().unwrap().to_string()
+    }
+
+    #[test]
+    fn asymmetric_algorithms() {
+        let (jwks, rsa, ec) = asymmetric_keys();
+        let mut client = discovered(MockHttpClient::default());
+        client.jwks = Some(jwks);
+        client.provider.0.id_token_signing_alg_values_supported = vec![
+            String::from("RS256"),
+            String::from("PS256"),
+            String::from("ES256"),
+        ];
+
+        for token in &[
+            bound_token(SignatureAlgorithm::RS256, "rsa", &rsa),
+            bound_token(SignatureAlgorithm::PS256, "rsa", &rsa),
+            bound_token(SignatureAlgorithm::ES256, "ec", &ec),
+        ] {
+            client
+                .verify_certificate_bound_token(token, b"abc")
+                .unwrap();
+        }
+
+        // The EC key only verifies ES256
+        client.provider.0.id_token_signing_alg_values_supported = vec![String::from("PS256")];
+        let token = bound_token(SignatureAlgorithm::PS256, "ec", &rsa);
+        match client.verify_certificate_bound_token(&token, b"abc") {
+            Err(Error::Jose(Jose::WrongKeyType { .. })) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        client.jwks = serde_json::from_value(json!({ "keys": [
+            { "kty": "EC", "kid": "ec", "crv": "P-521", "x": "AA", "y": "AA" }
+        ] }))
+        .ok();
+        match client.verify_certificate_bound_token(&token, b"abc") {
+            Err(Error::Decode(Decode::UnsupportedKey(curve))) => assert_eq!("P-521", curve),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn algorithm_allow_list() {
+        let token = |algorithm, secret: &[u8]| {
+            let header = RegisteredHeader {
+                algorithm,
+                key_id: Some(String::from("key")),
+                ..Default::default()
+            };
+            let claims = ConfirmationClaims { cnf: None };
+            let token = Compact::new_decoded(From::from(header), claims)
+                .encode(&Secret::Bytes(secret.to_vec()))
+                .unwrap();
+            token.encoded().unwrap().to_string()
+        };
+        let hs256 = token(SignatureAlgorithm::HS256, b"secret");
+        let mut client = discovered(MockHttpClient::default());
+
+        // HMAC is never allowed by default, even if advertised
+        client.provider.0.id_token_signing_alg_values_supported =
+            vec![String::from("RS256"), String::from("HS256")];
+        match client.verify_certificate_bound_token(&hs256, b"abc") {
+            Err(Error::Decode(Decode::DisallowedAlgorithm(alg))) => assert_eq!("HS256", alg),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        client.validation.algorithms = vec![SignatureAlgorithm::HS256, SignatureAlgorithm::None];
+        // Decodes, but has no confirmation
+        match client.verify_certificate_bound_token(&hs256, b"abc") {
+            Err(Error::Validation(Validation::Missing(Missing::CertificateThumbprint))) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        // {"alg":"none"}
+        let unsigned = format!("eyJhbGciOiJub25lIn0.{}.", hs256.split('.').nth(1).unwrap());
+        match client.verify_certificate_bound_token(&unsigned, b"abc") {
+            Err(Error::Decode(Decode::Unsigned)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // Keys for encryption are skipped, the only signing key verifies the token
+        client.jwks = serde_json::from_str(
+            r#"{ "keys": [
+                { "kty": "oct", "kid": "key", "k": "c2VjcmV0", "use": "enc" },
+                { "kty": "oct", "kid": "other", "k": "b3RoZXI", "key_ops": ["verify"] }
+            ] }"#,
+        )
+        .ok();
+        match client.verify_certificate_bound_token(&hs256, b"abc") {
+            Err(Error::Jose(_)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        let other = token(SignatureAlgorithm::HS256, b"other");
+        match client.verify_certificate_bound_token(&other, b"abc") {
+            Err(Error::Validation(Validation::Missing(Missing::CertificateThumbprint))) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        client.jwks = serde_json::from_str(
+            r#"{ "keys": [{ "kty": "oct", "kid": "key", "k": "c2VjcmV0", "key_ops": ["sign"] }] }"#,
+        )
+        .ok();
+        match client.verify_certificate_bound_token(&hs256, b"abc") {
+            Err(Error::Decode(Decode::NoSigningKey)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn no_safe_advertised_algorithm() {
+        let (jwks, rsa, _) = asymmetric_keys();
+        let mut client = discovered(MockHttpClient::default());
+        client.jwks = Some(jwks);
+        let token = bound_token(SignatureAlgorithm::PS256, "rsa", &rsa);
+
+        // A provider advertising only HMAC allows nothing by default
+        client.provider.0.id_token_signing_alg_values_supported = vec![String::from("HS256")];
+        match client.verify_certificate_bound_token(&token, b"abc") {
+            Err(Error::Decode(Decode::DisallowedAlgorithm(alg))) => assert_eq!("PS256", alg),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // One advertising nothing allows the safe ones
+        client.provider.0.id_token_signing_alg_values_supported = Vec::new();
+        client
+            .verify_certificate_bound_token(&token, b"abc")
+            .unwrap();
+    }
+
+    #[test]
+    fn metadata_round_trip() {
+        let metadata: Metadata = serde_json::from_str(METADATA).unwrap();
+        let client = discovered(MockHttpClient::default());
+        assert_eq!(
+            "https://example.com/token",
+            client.config().token_endpoint.as_str()
+        );
+        assert_eq!(Some(metadata), client.metadata());
+        assert_eq!(None, client.expires());
+    }
+
+    #[test]
+    fn offline_discovery_mode() {
+        let mut metadata: Metadata = serde_json::from_str(METADATA).unwrap();
+        metadata.mode = DiscoveryMode::OAuth;
+        metadata.options.allow_insecure = true;
+        let http_client = Arc::new(
+            MockHttpClient::default()
+                .with_json(
+                    "https://example.com/.well-known/oauth-authorization-server",
+                    &serde_json::to_string(&metadata.config).unwrap(),
+                )
+                .with_json("https://example.com/certs", r#"{ "keys": [] }"#),
+        );
+        let mut client: Client = Client::from_metadata(
+            String::from("foo"),
+            String::from("bar"),
+            None,
+            http_client.clone(),
+            metadata.clone(),
+        )
+        .unwrap();
+        assert_eq!(Some(&metadata), client.metadata().as_ref());
+
+        assert!(block_on(client.refresh()).unwrap());
+        let requests = http_client.requests.lock().unwrap();
+        assert_eq!(
+            "https://example.com/.well-known/oauth-authorization-server",
+            requests[0].url.as_str()
+        );
+        let metadata = client.metadata().unwrap();
+        assert_eq!(DiscoveryMode::OAuth, metadata.mode);
+        assert!(metadata.options.allow_insecure);
+    }
+
+    #[test]
+    fn refresh_periodically() {
+        let cached = |max_age: Option<&str>, body: &str| {
+            let mut headers = http::HeaderMap::new();
+            if let Some(max_age) = max_age {
+                headers.insert(http::header::CACHE_CONTROL, max_age.parse().unwrap());
+            }
+            HttpResponse {
+                status: StatusCode::OK,
+                headers,
+                body: body.as_bytes().to_vec(),
+            }
+        };
+        let config = r#"{
+            "issuer": "https://example.com",
+            "authorization_endpoint": "https://example.com/auth",
+            "token_endpoint": "https://example.com/v2/token",
+            "jwks_uri": "https://example.com/certs",
+            "response_types_supported": ["code"],
+            "subject_types_supported": ["public"],
+            "id_token_signing_alg_values_supported": ["RS256"]
+        }"#;
+        let http_client = MockHttpClient::default()
+            .with_response(
+                "https://example.com/.well-known/openid-configuration",
+                cached(Some("max-age=600"), config),
+            )
+            .with_response(
+                "https://example.com/certs",
+                cached(None, r#"{ "keys": [] }"#),
+            )
+            .with_response(
+                "https://example.com/certs",
+                HttpResponse {
+                    status: StatusCode::INTERNAL_SERVER_ERROR,
+                    headers: Default::default(),
+                    body: Vec::new(),
+                },
+            );
+        let client = Arc::new(RwLock::new(discovered(MockHttpClient::default())));
+        let waits = Mutex::new(Vec::new());
+        let task = Client::refresh_periodically(
+            client.clone(),
+            http_client,
+            Duration::from_secs(3600),
+            |wait| {
+                waits.lock().unwrap().push(wait);
+                futures::future::ready(())
+            },
+        );
+
+        // The key set has no expiry, so it is refetched once the config expires, and fails
+        match block_on(task) {
+            Err(Error::Http(_)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        let client = client.read().unwrap();
+        assert_eq!(
+            "https://example.com/v2/token",
+            client.config().token_endpoint.as_str()
+        );
+        assert_eq!(Some(0), client.jwks.as_ref().map(|jwks| jwks.keys.len()));
+
+        let waits = waits.lock().unwrap();
+        assert_eq!(2, waits.len());
+        assert_eq!(Duration::from_secs(3600), waits[0]);
+        assert!(waits[1] > Duration::from_secs(590) && waits[1] <= Duration::from_secs(600));
+    }
+
+    #[test]
+    fn introspect() {
+        let http_client = MockHttpClient::default().with_json(
+            "https://example.com/introspect",
+            r#"{ "active": true, "client_id": "foo", "scope": "openid" }"#,
+        );
+        let client = discovered(http_client);
+        let introspection = block_on(client.introspect("token", Some("access_token"))).unwrap();
+        assert!(introspection.active);
+        assert_eq!(Some("foo".to_string()), introspection.client_id);
+    }
+
+    #[test]
+    fn mtls_endpoint_aliases() {
+        let http_client = MockHttpClient::default().with_json(
+            "https://mtls.example.com/introspect",
+            r#"{ "active": true, "cnf": { "x5t#S256": "ungWv48Bz-pBQUDeXa4iI7ADYaOWF3qctBD_YfIAFa0" } }"#,
+        );
+        let mut client = discovered(http_client);
+        client.provider.0.mtls_endpoint_aliases = Some(MtlsEndpointAliases {
+            introspection_endpoint: Url::parse("https://mtls.example.com/introspect").ok(),
+            ..Default::default()
+        });
+        assert!(block_on(client.introspect("token", None)).is_err());
+
+        client.auth_method = ClientAuthMethod::TlsClientAuth;
+        let introspection = block_on(client.introspect("token", None)).unwrap();
+        assert!(introspection.verify_certificate(b"abc").is_ok());
+    }
+
+    #[test]
+    fn certificate_bound_token() {
+        let mut client = discovered(MockHttpClient::default());
+        client.validation.algorithms = vec![SignatureAlgorithm::HS256];
+        let header = RegisteredHeader {
+            algorithm: SignatureAlgorithm::HS256,
+            key_id: Some(String::from("key")),
+            ..Default::default()
+        };
+        let claims = ConfirmationClaims {
+            cnf: Some(Confirmation {
+                x5t_s256: Some(certificate_thumbprint(b"abc")),
+            }),
+        };
+        let token = Compact::new_decoded(From::from(header), claims)
+            .encode(&Secret::Bytes(b"secret".to_vec()))
+            .unwrap();
+        let token = token.encoded().unwrap().to_string();
+        assert!(client
+            .verify_certificate_bound_token(&token, b"abc")
+            .is_ok());
+        assert!(client
+            .verify_certificate_bound_token(&token, b"abd")
+            .is_err());
+    }
+
+    #[test]
+    fn dpop_nonce_retry() {
+        let challenge = HttpResponse {
+            status: StatusCode::BAD_REQUEST,
+            headers: std::iter::once((DPOP_NONCE, HeaderValue::from_static("server-nonce")))
+                .collect(),
+            body: br#"{ "error": "use_dpop_nonce" }"#.to_vec(),
+        };
+        let http_client = Arc::new(
+            MockHttpClient::default()
+                .with_response("https://example.com/token", challenge)
+                .with_json(
+                    "https://example.com/token",
+                    r#"{ "access_token": "token", "token_type": "DPoP" }"#,
+                )
+                .with_json("https://example.com/userinfo", r#"{ "sub": "foo" }"#),
+        );
+        let mut client = discovered(MockHttpClient::default());
+        client.http_client = Box::new(http_client.clone());
+        client.provider.0.userinfo_endpoint = Url::parse("https://example.com/userinfo").ok();
+        client.dpop = Some(DPoPKey::generate().unwrap().0);
+
+        let bearer = block_on(client.request_token("code")).unwrap();
+        assert!(bearer.is_dpop());
+        let token = Token::from(bearer);
+        block_on(client.request_userinfo(&token)).unwrap();
+
+        let requests = http_client.requests.lock().unwrap();
+        assert_eq!(3, requests.len());
+        let nonce = |request: &HttpRequest| {
+            let proof = request.headers.get(DPOP).unwrap().to_str().unwrap();
+            let payload = proof.split('.').nth(1).unwrap();
+            let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD).unwrap();
+            serde_json::from_slice::<serde_json::Value>(&payload).unwrap()["nonce"].clone()
+        };
+        assert_eq!(serde_json::Value::Null, nonce(&requests[0]));
+        assert_eq!("server-nonce", nonce(&requests[1]));
+        assert_eq!(
+            "DPoP token",
+            requests[2].headers.get(AUTHORIZATION).unwrap()
+        );
+        assert_eq!("server-nonce", nonce(&requests[2]));
+    }
+
+    #[test]
+    fn dpop_token_without_key() {
+        let http_client = Arc::new(MockHttpClient::default());
+        let mut client = discovered(MockHttpClient::default());
+        client.http_client = Box::new(http_client.clone());
+        let token: Bearer =
+            serde_json::from_str(r#"{ "access_token": "token", "token_type": "DPoP" }"#).unwrap();
+
+        let request = HttpRequest::get(Url::parse("https://api.example.com/").unwrap());
+        match block_on(client.send_with_token(request, &token)) {
+            Err(Error::Validation(Validation::Missing(Missing::DPoPKey))) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(http_client.requests.lock().unwrap().is_empty());
+    }
+
+    #[cfg(feature = "password-grant")]
+    #[test]
+    fn password_grant() {
+        let http_client = Arc::new(MockHttpClient::default().with_json(
+            "https://example.com/token",
+            r#"{ "access_token": "token", "token_type": "Bearer" }"#,
+        ));
+        let mut client = discovered(MockHttpClient::default());
+        client.http_client = Box::new(http_client.clone());
+
+        let token =
+            block_on(client.request_token_password("user", "pass word", Some("openid"))).unwrap();
+        assert_eq!("token", token.bearer.access_token);
+        assert!(token.id_token.is_none());
+
+        let requests = http_client.requests.lock().unwrap();
+        assert_eq!(
+            "grant_type=password&username=user&password=pass+word&scope=openid",
+            String::from_utf8(requests[0].body.clone()).unwrap()
+        );
+    }
+
+    #[test]
+    fn backchannel_poll() {
+        let error = |error: &str| HttpResponse {
+            status: StatusCode::BAD_REQUEST,
+            headers: Default::default(),
+            body: format!(r#"{{ "error": "{}" }}"#, error).into_bytes(),
+        };
+        let http_client = Arc::new(
+            MockHttpClient::default()
+                .with_json(
+                    "https://example.com/bc-authorize",
+                    r#"{ "auth_req_id": "1c266114", "expires_in": 120, "interval": 2 }"#,
+                )
+                .with_response("https://example.com/token", error("authorization_pending"))
+                .with_response("https://example.com/token", error("slow_down"))
+                .with_json(
+                    "https://example.com/token",
+                    r#"{ "access_token": "token", "token_type": "Bearer" }"#,
+                ),
+        );
+        let mut client = discovered(MockHttpClient::default());
+        client.http_client = Box::new(http_client.clone());
+        client.provider.0.backchannel_authentication_endpoint =
+            Url::parse("https://example.com/bc-authorize").ok();
+
+        let request = BackchannelAuthentication {
+            login_hint: Some(String::from("user@example.com")),
+            binding_message: Some(String::from("W4SCT")),
+            ..Default::default()
+        };
+        let mut response = block_on(client.backchannel_authenticate(&request)).unwrap();
+        assert_eq!("1c266114", response.auth_req_id);
+
+        assert!(block_on(client.backchannel_token(&mut response))
+            .unwrap()
+            .is_none());
+        assert!(block_on(client.backchannel_token(&mut response))
+            .unwrap()
+            .is_none());
+        assert_eq!(7, response.poll_interval().as_secs());
+        let token = block_on(client.backchannel_token(&mut response))
+            .unwrap()
+            .unwrap();
+        assert_eq!("token", token.bearer.access_token);
+
+        let requests = http_client.requests.lock().unwrap();
+        assert_eq!(
+            "scope=openid&login_hint=user%40example.com&binding_message=W4SCT",
+            String::from_utf8(requests[0].body.clone()).unwrap()
+        );
+        assert_eq!(
+            "grant_type=urn%3Aopenid%3Aparams%3Agrant-type%3Aciba&auth_req_id=1c266114",
+            String::from_utf8(requests[1].body.clone()).unwrap()
+        );
+    }
+
+    #[test]
+    fn backchannel_push() {
+        let now = Utc::now().timestamp();
+        let at_hash = |access_token: &str| {
+            let hash = ring::digest::digest(&ring::digest::SHA256, access_token.as_bytes());
+            base64::encode_config(&hash.as_ref()[..16], base64::URL_SAFE_NO_PAD)
+        };
+        let id_token = |auth_req_id: &str, at_hash: &str| {
+            let header = RegisteredHeader {
+                algorithm: SignatureAlgorithm::HS256,
+                key_id: Some(String::from("key")),
+                ..Default::default()
+            };
+            let claims = ClaimsSet {
+                registered: RegisteredClaims::default(),
+                private: json!({
+                    "iss": "https://example.com/",
+                    "sub": "248289761001",
+                    "aud": "foo",
+                    "exp": now + 600,
+                    "iat": now,
+                    "at_hash": at_hash,
+                    "urn:openid:params:jwt:claim:auth_req_id": auth_req_id
+                }),
+            };
+            let secret = Secret::Bytes(b"secret".to_vec());
+            Compact::new_decoded(From::from(header), claims)
+                .into_encoded(&secret)
+                .unwrap()
+                .unwrap_encoded()
+                .to_string()
+        };
+        let notification = |id_token: Option<String>| {
+            let mut body = json!({
+                "auth_req_id": "1c266114",
+                "access_token": "G5kXH2wHvUra0sHlDy1iTkDJgsgUO1bN",
+                "token_type": "Bearer"
+            });
+            if let Some(id_token) = id_token {
+                body["id_token"] = json!(id_token);
+            }
+            let body = serde_json::to_vec(&body).unwrap();
+            BackchannelNotification::parse(Some("Bearer token"), &body, "token").unwrap()
+        };
+        let mut client = discovered(MockHttpClient::default());
+        client.validation.algorithms = vec![SignatureAlgorithm::HS256];
+
+        let valid = id_token("1c266114", &at_hash("G5kXH2wHvUra0sHlDy1iTkDJgsgUO1bN"));
+        let token = client.backchannel_push(notification(Some(valid))).unwrap();
+        assert_eq!(
+            "G5kXH2wHvUra0sHlDy1iTkDJgsgUO1bN",
+            token.bearer.access_token
+        );
+        assert!(token.id_token.is_some());
+
+        match client.backchannel_push(notification(None)).err() {
+            Some(Error::Backchannel(Backchannel::NoIdToken)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        let other_request = id_token("5e7c04b2", &at_hash("G5kXH2wHvUra0sHlDy1iTkDJgsgUO1bN"));
+        match client
+            .backchannel_push(notification(Some(other_request)))
+            .err()
+        {
+            Some(Error::Backchannel(Backchannel::AuthReqIdMismatch { actual, .. })) => {
+                assert_eq!("5e7c04b2", actual)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        let other_token = id_token("1c266114", &at_hash("other"));
+        match client
+            .backchannel_push(notification(Some(other_token)))
+            .err()
+        {
+            Some(Error::Backchannel(Backchannel::TokenHashMismatch(name))) => {
+                assert_eq!("at_hash", name)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn resource_indicators() {
+        let http_client = Arc::new(MockHttpClient::default().with_json(
+            "https://example.com/token",
+            r#"{ "access_token": "token", "refresh_token": "refresh" }"#,
+        ));
+        let mut client = discovered(MockHttpClient::default());
+        client.http_client = Box::new(http_client.clone());
+        client.redirect_uri = Some(String::from("https://rp.example.com/cb"));
+
+        let options = Options {
+            resource: vec![
+                String::from("https://api.example.com/"),
+                String::from("https://other.example.com/"),
+            ],
+            ..Default::default()
+        };
+        options.validate(client.config()).unwrap();
+        assert!(client
+            .auth_url(&options)
+            .as_str()
+            .ends_with("&resource=https%3A%2F%2Fapi.example.com%2F&resource=https%3A%2F%2Fother.example.com%2F"));
+
+        let resources = ["https://api.example.com/"];
+        let bearer = block_on(client.request_token_with_resources("code", &resources)).unwrap();
+        block_on(client.refresh_token_with_resources(bearer, None, &resources)).unwrap();
+
+        let requests = http_client.requests.lock().unwrap();
+        assert_eq!(
+            "grant_type=authorization_code&code=code&redirect_uri=https%3A%2F%2Frp.example.com%2Fcb\
+             &resource=https%3A%2F%2Fapi.example.com%2F",
+            String::from_utf8(requests[0].body.clone()).unwrap()
+        );
+        assert_eq!(
+            "grant_type=refresh_token&refresh_token=refresh&resource=https%3A%2F%2Fapi.example.com%2F",
+            String::from_utf8(requests[1].body.clone()).unwrap()
+        );
+
+        let invalid = Options {
+            resource: vec![String::from("api#fragment")],
+            ..Default::default()
+        };
+        assert!(invalid.validate(client.config()).is_err());
+    }
+
+    #[test]
+    fn rich_authorization_request() {
+        let http_client = MockHttpClient::default().with_json(
+            "https://example.com/token",
+            r#"{
+                "access_token": "token",
+                "authorization_details": [{ "type": "account_information", "actions": ["read"] }]
+            }"#,
+        );
+        let mut client = discovered(http_client);
+        client.provider.0.authorization_details_types_supported =
+            Some(vec![String::from("account_information")]);
+
+        let mut detail = AuthorizationDetail::new("account_information");
+        detail.actions = vec![String::from("read")];
+        let options = Options {
+            authorization_details: Some(vec![detail.clone()]),
+            ..Default::default()
+        };
+        options.validate(client.config()).unwrap();
+        let url = client.auth_url(&options);
+        let (_, details) = url
+            .query_pairs()
+            .find(|(name, _)| name == "authorization_details")
+            .unwrap();
+        assert_eq!(
+            r#"[{"type":"account_information","actions":["read"]}]"#,
+            details
+        );
+
+        let bearer = block_on(client.request_token("code")).unwrap();
+        assert_eq!(Some(vec![detail]), bearer.authorization_details);
+
+        client.provider.0.authorization_details_types_supported = None;
+        assert!(options.validate(client.config()).is_err());
+    }
+
+    #[test]
+    fn claims_request() {
+        let mut client = discovered(MockHttpClient::default());
+        let options = Options {
+            claims: Some(
+                ClaimsRequest::default().id_token_claim("acr", ClaimRequest::default().essential()),
+            ),
+            ..Default::default()
+        };
+        assert!(options.validate(client.config()).is_err());
+        client.provider.0.claims_parameter_supported = true;
+        options.validate(client.config()).unwrap();
+
+        let url = client.auth_url(&options);
+        let (_, claims) = url
+            .query_pairs()
+            .find(|(name, _)| name == "claims")
+            .unwrap();
+        assert_eq!(r#"{"id_token":{"acr":{"essential":true}}}"#, claims);
+    }
+
+    #[test]
+    fn jwt_bearer() {
+        let http_client = Arc::new(MockHttpClient::default().with_json(
+            "https://example.com/token",
+            r#"{ "access_token": "token", "token_type": "Bearer", "expires_in": 3600 }"#,
+        ));
+        let mut client = discovered(MockHttpClient::default());
+        client.http_client = Box::new(http_client.clone());
+
+        let key = AssertionKey::hmac("service@example.com", "secret");
+        let bearer = block_on(client.request_token_jwt_bearer(&key, "user", Some("read"))).unwrap();
+        assert_eq!("token", bearer.access_token);
+
+        let requests = http_client.requests.lock().unwrap();
+        let body = String::from_utf8(requests[0].body.clone()).unwrap();
+        let params: HashMap<_, _> = form_urlencoded::parse(body.as_bytes()).collect();
+        assert_eq!(
+            "urn:ietf:params:oauth:grant-type:jwt-bearer",
+            params["grant_type"]
+        );
+        assert_eq!("read", params["scope"]);
+        let assertion: Compact<AssertionClaims, Empty> = Compact::new_encoded(&params["assertion"]);
+        let assertion = assertion
+            .decode(
+                &Secret::Bytes(b"secret".to_vec()),
+                SignatureAlgorithm::HS256,
+            )
+            .unwrap();
+        let claims = assertion.payload().unwrap();
+        assert_eq!("service@example.com", claims.iss);
+        assert_eq!("user", claims.sub);
+        assert_eq!("https://example.com/token", claims.aud);
+        assert!(claims.exp > claims.iat);
+        assert!(requests[0].headers.contains_key(AUTHORIZATION));
+        drop(requests);
+
+        // A service account without a secret does not authenticate the client
+        client.client_secret = String::new();
+        block_on(client.request_token_jwt_bearer(&key, "user", None)).unwrap();
+        let requests = http_client.requests.lock().unwrap();
+        assert!(!requests[1].headers.contains_key(AUTHORIZATION));
+        let body = String::from_utf8(requests[1].body.clone()).unwrap();
+        assert!(!body.contains("client_secret"));
+    }
+
+    #[test]
+    fn exchange_token() {
+        let http_client = Arc::new(MockHttpClient::default().with_json(
+            "https://example.com/token",
+            r#"{
+                "access_token": "downstream",
+                "issued_token_type": "urn:ietf:params:oauth:token-type:access_token",
+                "token_type": "Bearer",
+                "expires_in": 60
+            }"#,
+        ));
+        let mut client = discovered(MockHttpClient::default());
+        client.http_client = Box::new(http_client.clone());
+
+        let mut exchange = TokenExchange::access_token("upstream");
+        exchange.audience = vec![String::from("downstream-service")];
+        exchange.requested_token_type = Some(TokenType::AccessToken);
+        let bearer = block_on(client.exchange_token(&exchange)).unwrap();
+        assert_eq!("downstream", bearer.access_token);
+        assert_eq!(Some(TokenType::AccessToken), bearer.issued_token_type);
+
+        let requests = http_client.requests.lock().unwrap();
+        let body = String::from_utf8(requests[0].body.clone()).unwrap();
+        assert_eq!(
+            "grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Atoken-exchange\
+             &subject_token=upstream\
+             &subject_token_type=urn%3Aietf%3Aparams%3Aoauth%3Atoken-type%3Aaccess_token\
+             &audience=downstream-service\
+             &requested_token_type=urn%3Aietf%3Aparams%3Aoauth%3Atoken-type%3Aaccess_token",
+            body
+        );
+    }
+
     #[test]
     fn auth_uri() {
-        let http_client = reqwest::Client::new();
+        let http_client = MockHttpClient::default();
         let client: Client<_> = Client::new(
             Test::new(),
             String::from("foo"),
@@ -584,7 +2340,7 @@ mod tests {
 
     #[test]
     fn auth_uri_with_redirect_uri() {
-        let http_client = reqwest::Client::new();
+        let http_client = MockHttpClient::default();
         let client: Client<_> = Client::new(
             Test::new(),
             String::from("foo"),
@@ -601,7 +2357,7 @@ mod tests {
 
     #[test]
     fn auth_uri_with_scope() {
-        let http_client = reqwest::Client::new();
+        let http_client = MockHttpClient::default();
         let client: Client<_> = Client::new(
             Test::new(),
             String::from("foo"),
@@ -618,7 +2374,7 @@ mod tests {
 
     #[test]
     fn auth_uri_with_state() {
-        let http_client = reqwest::Client::new();
+        let http_client = MockHttpClient::default();
         let client: Client<_> = Client::new(
             Test::new(),
             String::from("foo"),
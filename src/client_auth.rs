@@ -0,0 +1,40 @@
+/// How the client authenticates at the token, introspection and other endpoints.
+///
+/// See [spec 9](https://openid.net/specs/openid-connect-core-1_0.html#ClientAuthentication) and
+/// [RFC 8705, section 2](https://tools.ietf.org/html/rfc8705#section-2).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ClientAuthMethod {
+    /// `client_id` and `client_secret` in an HTTP Basic `Authorization` header. Also sent in the
+    /// body if the provider requires `credentials_in_body`.
+    #[default]
+    ClientSecretBasic,
+    /// `client_id` and `client_secret` in the request body.
+    ClientSecretPost,
+    /// Mutual TLS with a CA issued client certificate configured on the HTTP client. Only the
+    /// `client_id` is sent in the body.
+    TlsClientAuth,
+    /// Mutual TLS with a self-signed client certificate configured on the HTTP client. Only the
+    /// `client_id` is sent in the body.
+    SelfSignedTlsClientAuth,
+}
+
+impl ClientAuthMethod {
+    pub fn as_str(&self) -> &'static str {
+        use ClientAuthMethod::*;
+        match *self {
+            ClientSecretBasic => "client_secret_basic",
+            ClientSecretPost => "client_secret_post",
+            TlsClientAuth => "tls_client_auth",
+            SelfSignedTlsClientAuth => "self_signed_tls_client_auth",
+        }
+    }
+
+    /// Whether the client authenticates with a TLS client certificate, in which case the
+    /// `mtls_endpoint_aliases` of the provider are used.
+    pub fn is_mtls(&self) -> bool {
+        matches!(
+            *self,
+            ClientAuthMethod::TlsClientAuth | ClientAuthMethod::SelfSignedTlsClientAuth
+        )
+    }
+}
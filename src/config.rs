@@ -1,15 +1,17 @@
+use crate::{GrantType, ResponseMode, ResponseType, SubjectType, TokenDeliveryMode};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
 // TODO I wish we could impl default for this, but you cannot have a config without issuer etc
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Config {
     pub issuer: Url,
     pub authorization_endpoint: Url,
     // Only optional in the implicit flow
     // TODO For now, we only support code flows.
     pub token_endpoint: Url,
-    #[serde(default)]
+    // RFC 8414 calls this introspection_endpoint
+    #[serde(default, alias = "introspection_endpoint")]
     pub token_introspection_endpoint: Option<Url>,
     #[serde(default)]
     pub userinfo_endpoint: Option<Url>,
@@ -21,19 +23,22 @@ pub struct Config {
     #[serde(default)]
     pub scopes_supported: Option<Vec<String>>,
     // There are only three valid response types, plus combinations of them, and none
-    // If we want to make these user friendly we want a struct to represent all 7 types
-    pub response_types_supported: Vec<String>,
-    // There are only two possible values here, query and fragment. Default is both.
+    pub response_types_supported: Vec<ResponseType>,
+    // query and fragment by spec, form_post by extension. Default is query and fragment.
     #[serde(default)]
-    pub response_modes_supported: Option<Vec<String>>,
-    // Must support at least authorization_code and implicit.
+    pub response_modes_supported: Option<Vec<ResponseMode>>,
+    // Default is authorization_code and implicit.
     #[serde(default)]
-    pub grant_types_supported: Option<Vec<String>>,
+    pub grant_types_supported: Option<Vec<GrantType>>,
     #[serde(default)]
     pub acr_values_supported: Option<Vec<String>>,
     // pairwise and public are valid by spec, but servers can add more
-    pub subject_types_supported: Vec<String>,
+    // Required by OpenID Connect Discovery, but not by RFC 8414
+    #[serde(default)]
+    pub subject_types_supported: Vec<SubjectType>,
     // Must include at least RS256, none is only allowed with response types without id tokens
+    // Required by OpenID Connect Discovery, but not by RFC 8414
+    #[serde(default)]
     pub id_token_signing_alg_values_supported: Vec<String>,
     #[serde(default)]
     pub id_token_encryption_alg_values_supported: Option<Vec<String>>,
@@ -87,9 +92,150 @@ pub struct Config {
     // This is a NONSTANDARD extension Google uses that is a part of the Oauth discovery draft
     #[serde(default)]
     pub code_challenge_methods_supported: Option<Vec<String>>,
+    // RFC 8705
+    #[serde(default)]
+    pub tls_client_certificate_bound_access_tokens: bool,
+    #[serde(default)]
+    pub mtls_endpoint_aliases: Option<MtlsEndpointAliases>,
+    // RFC 9449
+    #[serde(default)]
+    pub dpop_signing_alg_values_supported: Option<Vec<String>>,
+    // OpenID Connect CIBA
+    #[serde(default)]
+    pub backchannel_authentication_endpoint: Option<Url>,
+    #[serde(default)]
+    pub backchannel_token_delivery_modes_supported: Option<Vec<TokenDeliveryMode>>,
+    #[serde(default)]
+    pub backchannel_authentication_request_signing_alg_values_supported: Option<Vec<String>>,
+    #[serde(default)]
+    pub backchannel_user_code_parameter_supported: bool,
+    // RFC 9396
+    #[serde(default)]
+    pub authorization_details_types_supported: Option<Vec<String>>,
+}
+
+/// Endpoints to use instead of the regular ones when authenticating with mutual TLS.
+///
+/// See [RFC 8705, section 5](https://tools.ietf.org/html/rfc8705#section-5).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct MtlsEndpointAliases {
+    #[serde(default)]
+    pub token_endpoint: Option<Url>,
+    #[serde(default)]
+    pub revocation_endpoint: Option<Url>,
+    #[serde(default)]
+    pub introspection_endpoint: Option<Url>,
+    #[serde(default)]
+    pub userinfo_endpoint: Option<Url>,
+    #[serde(default)]
+    pub device_authorization_endpoint: Option<Url>,
+    #[serde(default)]
+    pub pushed_authorization_request_endpoint: Option<Url>,
+    #[serde(default)]
+    pub backchannel_authentication_endpoint: Option<Url>,
+}
+
+impl Config {
+    /// Whether the provider advertises the given response_type.
+    pub fn supports_response_type(&self, response_type: ResponseType) -> bool {
+        self.response_types_supported.contains(&response_type)
+    }
+
+    /// Whether the provider advertises the given response_mode. If the provider omits
+    /// `response_modes_supported`, only query and fragment are assumed.
+    pub fn supports_response_mode(&self, response_mode: ResponseMode) -> bool {
+        match self.response_modes_supported {
+            Some(ref modes) => modes.contains(&response_mode),
+            None => response_mode == ResponseMode::Query || response_mode == ResponseMode::Fragment,
+        }
+    }
+
+    /// Whether the provider advertises the given grant type. If the provider omits
+    /// `grant_types_supported`, only authorization_code and implicit are assumed.
+    pub fn supports_grant(&self, grant_type: GrantType) -> bool {
+        match self.grant_types_supported {
+            Some(ref grants) => grants.contains(&grant_type),
+            None => grant_type == GrantType::AuthorizationCode || grant_type == GrantType::Implicit,
+        }
+    }
+
+    /// Whether the provider advertises the given authorization details type. If the provider
+    /// omits `authorization_details_types_supported`, none are assumed.
+    pub fn supports_authorization_details_type(&self, detail_type: &str) -> bool {
+        match self.authorization_details_types_supported {
+            Some(ref types) => types.iter().any(|supported| supported == detail_type),
+            None => false,
+        }
+    }
+
+    /// Whether the provider advertises the given subject identifier type.
+    pub fn supports_subject_type(&self, subject_type: SubjectType) -> bool {
+        self.subject_types_supported.contains(&subject_type)
+    }
 }
 
 // This seems really dumb...
 fn tru() -> bool {
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+    use crate::{GrantType, ResponseMode, ResponseType, SubjectType};
+
+    const CONFIG: &str = r#"
+        {
+            "issuer": "https://server.example.com",
+            "authorization_endpoint": "https://server.example.com/connect/authorize",
+            "token_endpoint": "https://server.example.com/connect/token",
+            "jwks_uri": "https://server.example.com/jwks.json",
+            "response_types_supported": ["code", "id_token code", "urn:example:custom"],
+            "response_modes_supported": ["query", "form_post"],
+            "grant_types_supported": [
+                "authorization_code",
+                "urn:ietf:params:oauth:grant-type:device_code"
+            ],
+            "subject_types_supported": ["public", "pairwise"],
+            "id_token_signing_alg_values_supported": ["RS256"]
+        }
+    "#;
+
+    #[test]
+    fn typed_supported_values() {
+        let config: Config = serde_json::from_str(CONFIG).unwrap();
+        assert!(config.supports_response_type(ResponseType::CodeIdToken));
+        assert!(!config.supports_response_type(ResponseType::Token));
+        assert_eq!(
+            ResponseType::Other("urn:example:custom".to_string()),
+            config.response_types_supported[2]
+        );
+        assert!(config.supports_response_mode(ResponseMode::FormPost));
+        assert!(!config.supports_response_mode(ResponseMode::Fragment));
+        assert!(config.supports_grant(GrantType::DeviceCode));
+        assert!(!config.supports_grant(GrantType::Implicit));
+        assert!(config.supports_subject_type(SubjectType::Pairwise));
+    }
+
+    #[test]
+    fn defaults_when_omitted() {
+        let mut config: Config = serde_json::from_str(CONFIG).unwrap();
+        config.response_modes_supported = None;
+        config.grant_types_supported = None;
+        assert!(config.supports_response_mode(ResponseMode::Fragment));
+        assert!(!config.supports_response_mode(ResponseMode::FormPost));
+        assert!(config.supports_grant(GrantType::Implicit));
+        assert!(!config.supports_grant(GrantType::RefreshToken));
+    }
+
+    #[test]
+    fn round_trip() {
+        let config: Config = serde_json::from_str(CONFIG).unwrap();
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!("code id_token", json["response_types_supported"][1]);
+        assert_eq!(
+            "urn:ietf:params:oauth:grant-type:device_code",
+            json["grant_types_supported"][1]
+        );
+    }
+}
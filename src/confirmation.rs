@@ -0,0 +1,93 @@
+use crate::error::{Error, Mismatch, Missing, Validation};
+use biscuit::CompactJson;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+
+/// Confirmation claim (`cnf`) binding a token to a key or certificate of its holder.
+///
+/// See [RFC 7800](https://tools.ietf.org/html/rfc7800) and
+/// [RFC 8705, section 3.1](https://tools.ietf.org/html/rfc8705#section-3.1).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Confirmation {
+    /// Base64url encoded SHA-256 thumbprint of the DER encoded X.509 certificate the token is
+    /// bound to.
+    #[serde(rename = "x5t#S256", default, skip_serializing_if = "Option::is_none")]
+    pub x5t_s256: Option<String>,
+}
+
+impl Confirmation {
+    /// Checks the token is bound to the given DER encoded client certificate, the one presented
+    /// in the mutual TLS connection the token was used on. Errors are:
+    ///
+    /// - Validation::Missing::CertificateThumbprint if the token is not certificate bound
+    /// - Validation::Mismatch::CertificateThumbprint if it is bound to another certificate
+    pub fn verify_certificate(&self, certificate: &[u8]) -> Result<(), Error> {
+        match self.x5t_s256 {
+            Some(ref expected) => {
+                let actual = certificate_thumbprint(certificate);
+                if expected != &actual {
+                    let expected = expected.clone();
+                    return Err(Validation::Mismatch(Mismatch::CertificateThumbprint {
+                        expected,
+                        actual,
+                    })
+                    .into());
+                }
+                Ok(())
+            }
+            None => Err(Validation::Missing(Missing::CertificateThumbprint).into()),
+        }
+    }
+}
+
+/// Checks an optional confirmation claim is bound to the given certificate.
+pub(crate) fn verify_certificate(
+    cnf: Option<&Confirmation>,
+    certificate: &[u8],
+) -> Result<(), Error> {
+    match cnf {
+        Some(cnf) => cnf.verify_certificate(certificate),
+        None => Err(Validation::Missing(Missing::CertificateThumbprint).into()),
+    }
+}
+
+/// The `x5t#S256` thumbprint of a DER encoded X.509 certificate.
+pub fn certificate_thumbprint(certificate: &[u8]) -> String {
+    base64::encode_config(
+        digest(&SHA256, certificate).as_ref(),
+        base64::URL_SAFE_NO_PAD,
+    )
+}
+
+/// Just the confirmation claim of a JWT access token.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct ConfirmationClaims {
+    #[serde(default)]
+    pub cnf: Option<Confirmation>,
+}
+
+impl CompactJson for ConfirmationClaims {}
+
+#[cfg(test)]
+mod tests {
+    use super::{certificate_thumbprint, Confirmation};
+
+    #[test]
+    fn thumbprint() {
+        assert_eq!(
+            "ungWv48Bz-pBQUDeXa4iI7ADYaOWF3qctBD_YfIAFa0",
+            certificate_thumbprint(b"abc")
+        );
+    }
+
+    #[test]
+    fn verify() {
+        let cnf: Confirmation = serde_json::from_str(
+            r#"{ "x5t#S256": "ungWv48Bz-pBQUDeXa4iI7ADYaOWF3qctBD_YfIAFa0" }"#,
+        )
+        .unwrap();
+        assert!(cnf.verify_certificate(b"abc").is_ok());
+        assert!(cnf.verify_certificate(b"abd").is_err());
+        assert!(Confirmation::default().verify_certificate(b"abc").is_err());
+    }
+}
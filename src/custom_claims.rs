@@ -6,6 +6,7 @@ use serde::{de::DeserializeOwned, Serialize};
 /// # Examples
 ///
 /// ```
+/// # #[cfg(feature = "reqwest")] {
 /// use openid::{Claims, CompactJson, CustomClaims, StandardClaims, Client, Discovered};
 /// use openid::provider::google::Installed;
 /// use serde::{Deserialize, Serialize};
@@ -32,6 +33,7 @@ use serde::{de::DeserializeOwned, Serialize};
 ///     Some(String::from("urn:ietf:wg:oauth:2.0:oob")),
 ///     reqwest::Client::new(), None,
 /// );
+/// # }
 /// ```
 ///
 /// See full example: [openid-example:custom_claims](https://github.com/kilork/openid-example/blob/master/examples/custom_claims.rs)
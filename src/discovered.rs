@@ -1,7 +1,12 @@
-use crate::{error::Error, Config, Provider};
+use crate::{
+    cache::{self, CacheControl, Fetched},
+    error::{Discovery, Error},
+    http_client::HttpClient,
+    Config, Provider,
+};
 use biscuit::jwk::JWKSet;
 use biscuit::Empty;
-use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use url::Url;
 
 pub struct Discovered(pub Config);
@@ -14,20 +19,407 @@ impl Provider for Discovered {
     fn token_uri(&self) -> &Url {
         &self.0.token_endpoint
     }
+
+    fn mtls_token_uri(&self) -> Option<&Url> {
+        self.0
+            .mtls_endpoint_aliases
+            .as_ref()
+            .and_then(|aliases| aliases.token_endpoint.as_ref())
+    }
+}
+
+/// Where to look for the provider metadata of an issuer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DiscoveryMode {
+    /// `{issuer}/.well-known/openid-configuration`, see
+    /// [OpenID Connect Discovery 1.0, section 4](https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderConfig).
+    #[default]
+    #[serde(rename = "openid")]
+    OpenId,
+    /// `/.well-known/oauth-authorization-server` inserted between the host and the path of the
+    /// issuer, see [RFC 8414, section 3](https://tools.ietf.org/html/rfc8414#section-3).
+    #[serde(rename = "oauth")]
+    OAuth,
 }
 
-pub async fn discover(client: &Client, mut issuer: Url) -> Result<Config, Error> {
-    issuer
-        .path_segments_mut()
-        .map_err(|_| Error::CannotBeABase)?
-        .extend(&[".well-known", "openid-configuration"]);
-    let resp = client.get(issuer).send().await?;
-    resp.json().await.map_err(Error::from)
+impl DiscoveryMode {
+    /// The metadata url for the given issuer.
+    pub fn well_known(&self, issuer: &Url) -> Result<Url, Error> {
+        let mut url = issuer.clone();
+        match *self {
+            DiscoveryMode::OpenId => {
+                url.path_segments_mut()
+                    .map_err(|_| Error::CannotBeABase)?
+                    .pop_if_empty()
+                    .extend(&[".well-known", "openid-configuration"]);
+            }
+            DiscoveryMode::OAuth => {
+                if issuer.cannot_be_a_base() {
+                    return Err(Error::CannotBeABase);
+                }
+                // The issuer path is kept as is, including a trailing slash. A lone slash is
+                // just an issuer without a path.
+                let path = match issuer.path() {
+                    "/" => "",
+                    path => path,
+                };
+                url.set_path(&format!("/.well-known/oauth-authorization-server{}", path));
+            }
+        }
+        Ok(url)
+    }
+}
+
+/// Optional parameters for discovery. Remember to ..Default::default() after you specify what
+/// you want.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DiscoveryOptions {
+    /// Accept an issuer and endpoints that are not https. Only use this for local development
+    /// issuers, never in production!
+    pub allow_insecure: bool,
+    /// Metadata locations to try, in order. The first document that can be fetched is used.
+    /// By default OpenID Connect Discovery is tried first, then RFC 8414.
+    pub modes: Vec<DiscoveryMode>,
+}
+
+impl Default for DiscoveryOptions {
+    fn default() -> Self {
+        Self {
+            allow_insecure: false,
+            modes: vec![DiscoveryMode::OpenId, DiscoveryMode::OAuth],
+        }
+    }
+}
+
+/// Where the provider metadata and key set came from and how long they may be cached.
+#[derive(Debug, Clone)]
+pub(crate) struct MetadataCache {
+    pub issuer: Url,
+    pub url: Url,
+    pub mode: DiscoveryMode,
+    pub options: DiscoveryOptions,
+    pub config: CacheControl,
+    pub jwks: CacheControl,
+}
+
+impl MetadataCache {
+    /// When the first of the cached documents with a known expiry becomes stale.
+    pub fn expires(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        match (self.config.expires, self.jwks.expires) {
+            (Some(config), Some(jwks)) => Some(config.min(jwks)),
+            (expires, None) | (None, expires) => expires,
+        }
+    }
 }
 
-/// Get the JWK set from the given Url. Errors are either a reqwest error or an Insecure error if
-/// the url isn't https.
-pub async fn jwks(client: &Client, url: Url) -> Result<JWKSet<Empty>, Error> {
-    let resp = client.get(url).send().await?;
-    resp.json().await.map_err(Error::from)
+impl MetadataCache {
+    /// A cache for metadata that did not come from the network. Everything is stale, so the
+    /// first refresh fetches the document of the config issuer with the given discovery mode.
+    pub fn offline(
+        config: &Config,
+        mode: DiscoveryMode,
+        options: &DiscoveryOptions,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            issuer: config.issuer.clone(),
+            url: mode.well_known(&config.issuer)?,
+            mode,
+            options: options.clone(),
+            config: CacheControl::default(),
+            jwks: CacheControl::default(),
+        })
+    }
+}
+
+/// Get the metadata document of the given issuer and validate it as required by
+/// [spec 4.3](https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderConfigurationValidation)
+/// or [RFC 8414, section 3.3](https://tools.ietf.org/html/rfc8414#section-3.3).
+/// Each of the configured modes is tried in order until a document can be fetched. Errors are:
+///
+/// - Error::Http if something goes wrong getting the document with every mode
+/// - Error::Json if the response is not a valid Config document with every mode
+/// - Discovery::NoModes if no discovery modes are configured
+/// - any error from `validate` for the first document fetched
+pub(crate) async fn discover(
+    client: &dyn HttpClient,
+    issuer: Url,
+    options: &DiscoveryOptions,
+) -> Result<(Config, MetadataCache), Error> {
+    let mut last_error = None;
+    for mode in &options.modes {
+        let url = mode.well_known(&issuer)?;
+        match cache::fetch(client, url.clone(), None).await {
+            Ok(Fetched::Modified(config, cache)) => {
+                validate(&config, &issuer, *mode, options)?;
+                let cache = MetadataCache {
+                    issuer,
+                    url,
+                    mode: *mode,
+                    options: options.clone(),
+                    config: cache,
+                    jwks: CacheControl::default(),
+                };
+                return Ok((config, cache));
+            }
+            Ok(Fetched::NotModified(_)) => unreachable!("no validators were sent"),
+            Err(error) => last_error = Some(error),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| Discovery::NoModes.into()))
+}
+
+/// Refetch whatever is stale in the cache with conditional requests. The key set is also
+/// refetched if the `jwks_uri` changed. Returns true if the config or key set changed.
+pub(crate) async fn refresh(
+    client: &dyn HttpClient,
+    cache: &mut MetadataCache,
+    config: &mut Config,
+    jwks: &mut Option<JWKSet<Empty>>,
+) -> Result<bool, Error> {
+    let mut jwks_uri_changed = false;
+    let mut changed = false;
+
+    if !cache.config.is_fresh() {
+        match cache::fetch::<Config>(client, cache.url.clone(), Some(&cache.config)).await? {
+            Fetched::Modified(new_config, new_cache) => {
+                validate(&new_config, &cache.issuer, cache.mode, &cache.options)?;
+                jwks_uri_changed = new_config.jwks_uri != config.jwks_uri;
+                *config = new_config;
+                cache.config = new_cache;
+                changed = true;
+            }
+            Fetched::NotModified(new_cache) => cache.config = new_cache,
+        }
+    }
+
+    if jwks_uri_changed || jwks.is_none() || !cache.jwks.is_fresh() {
+        let cached = if jwks_uri_changed || jwks.is_none() {
+            None
+        } else {
+            Some(&cache.jwks)
+        };
+        match cache::fetch(client, config.jwks_uri.clone(), cached).await? {
+            Fetched::Modified(new_jwks, new_cache) => {
+                *jwks = Some(new_jwks);
+                cache.jwks = new_cache;
+                changed = true;
+            }
+            Fetched::NotModified(new_cache) => cache.jwks = new_cache,
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Validate a discovery document against the issuer it was requested for. Errors are:
+///
+/// - Discovery::IssuerMismatch if the issuer in the document isn't exactly the requested one
+/// - Error::Insecure if the issuer or any endpoint isn't https, unless `allow_insecure` is set
+/// - Discovery::MissingRs256 if RS256 isn't a supported ID token signing algorithm, only checked
+///   for OpenID Connect discovery documents
+pub fn validate(
+    config: &Config,
+    issuer: &Url,
+    mode: DiscoveryMode,
+    options: &DiscoveryOptions,
+) -> Result<(), Error> {
+    if &config.issuer != issuer {
+        let expected = issuer.as_str().to_string();
+        let actual = config.issuer.as_str().to_string();
+        return Err(Discovery::IssuerMismatch { expected, actual }.into());
+    }
+
+    if !options.allow_insecure {
+        let endpoints = [
+            Some(&config.issuer),
+            Some(&config.authorization_endpoint),
+            Some(&config.token_endpoint),
+            Some(&config.jwks_uri),
+            config.token_introspection_endpoint.as_ref(),
+            config.userinfo_endpoint.as_ref(),
+            config.end_session_endpoint.as_ref(),
+            config.registration_endpoint.as_ref(),
+            config.backchannel_authentication_endpoint.as_ref(),
+        ];
+        let aliases = config.mtls_endpoint_aliases.as_ref().map(|aliases| {
+            vec![
+                aliases.token_endpoint.as_ref(),
+                aliases.introspection_endpoint.as_ref(),
+                aliases.userinfo_endpoint.as_ref(),
+                aliases.backchannel_authentication_endpoint.as_ref(),
+            ]
+        });
+        let endpoints = endpoints.iter().chain(aliases.iter().flatten());
+        for url in endpoints.flatten() {
+            if url.scheme() != "https" {
+                return Err(Error::Insecure((*url).clone()));
+            }
+        }
+    }
+
+    if mode == DiscoveryMode::OpenId
+        && !config
+            .id_token_signing_alg_values_supported
+            .iter()
+            .any(|alg| alg == "RS256")
+    {
+        return Err(Discovery::MissingRs256.into());
+    }
+
+    Ok(())
+}
+
+/// Get the JWK set from the given Url, with its caching information. Errors are either an
+/// HTTP error or a JSON error if the response is not a key set.
+pub(crate) async fn jwks(
+    client: &dyn HttpClient,
+    url: Url,
+) -> Result<(JWKSet<Empty>, CacheControl), Error> {
+    match cache::fetch(client, url, None).await? {
+        Fetched::Modified(jwks, cache) => Ok((jwks, cache)),
+        Fetched::NotModified(_) => unreachable!("no validators were sent"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, DiscoveryMode, DiscoveryOptions, MetadataCache};
+    use crate::{cache::CacheControl, error::Discovery, error::Error, Config};
+    use chrono::{TimeZone, Utc};
+    use url::Url;
+
+    fn config(issuer: &str, alg: &str) -> Config {
+        let json = format!(
+            r#"{{
+                "issuer": "{issuer}",
+                "authorization_endpoint": "{issuer}/auth",
+                "token_endpoint": "{issuer}/token",
+                "jwks_uri": "{issuer}/certs",
+                "response_types_supported": ["code"],
+                "subject_types_supported": ["public"],
+                "id_token_signing_alg_values_supported": ["{alg}"]
+            }}"#,
+            issuer = issuer,
+            alg = alg
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn valid() {
+        let issuer = Url::parse("https://example.com/realms/test").unwrap();
+        let config = config("https://example.com/realms/test", "RS256");
+        validate(
+            &config,
+            &issuer,
+            DiscoveryMode::OpenId,
+            &DiscoveryOptions::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn issuer_mismatch() {
+        let issuer = Url::parse("https://example.com/realms/test").unwrap();
+        let config = config("https://evil.example.com/realms/test", "RS256");
+        match validate(
+            &config,
+            &issuer,
+            DiscoveryMode::OpenId,
+            &DiscoveryOptions::default(),
+        ) {
+            Err(Error::Discovery(Discovery::IssuerMismatch { .. })) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn insecure() {
+        let issuer = Url::parse("http://localhost:8080").unwrap();
+        let config = config("http://localhost:8080", "RS256");
+        match validate(
+            &config,
+            &issuer,
+            DiscoveryMode::OpenId,
+            &DiscoveryOptions::default(),
+        ) {
+            Err(Error::Insecure(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let options = DiscoveryOptions {
+            allow_insecure: true,
+            ..Default::default()
+        };
+        validate(&config, &issuer, DiscoveryMode::OpenId, &options).unwrap();
+    }
+
+    #[test]
+    fn missing_rs256() {
+        let issuer = Url::parse("https://example.com").unwrap();
+        let config = config("https://example.com", "ES256");
+        match validate(
+            &config,
+            &issuer,
+            DiscoveryMode::OpenId,
+            &DiscoveryOptions::default(),
+        ) {
+            Err(Error::Discovery(Discovery::MissingRs256)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        validate(
+            &config,
+            &issuer,
+            DiscoveryMode::OAuth,
+            &DiscoveryOptions::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn well_known() {
+        let issuer = Url::parse("https://example.com").unwrap();
+        assert_eq!(
+            "https://example.com/.well-known/openid-configuration",
+            DiscoveryMode::OpenId.well_known(&issuer).unwrap().as_str()
+        );
+        assert_eq!(
+            "https://example.com/.well-known/oauth-authorization-server",
+            DiscoveryMode::OAuth.well_known(&issuer).unwrap().as_str()
+        );
+
+        let issuer = Url::parse("https://example.com/issuer1").unwrap();
+        assert_eq!(
+            "https://example.com/issuer1/.well-known/openid-configuration",
+            DiscoveryMode::OpenId.well_known(&issuer).unwrap().as_str()
+        );
+        assert_eq!(
+            "https://example.com/.well-known/oauth-authorization-server/issuer1",
+            DiscoveryMode::OAuth.well_known(&issuer).unwrap().as_str()
+        );
+
+        let issuer = Url::parse("https://example.com/tenant/").unwrap();
+        assert_eq!(
+            "https://example.com/.well-known/oauth-authorization-server/tenant/",
+            DiscoveryMode::OAuth.well_known(&issuer).unwrap().as_str()
+        );
+    }
+
+    #[test]
+    fn earliest_known_expiry() {
+        let config = config("https://example.com", "RS256");
+        let mut cache =
+            MetadataCache::offline(&config, DiscoveryMode::OpenId, &Default::default()).unwrap();
+        assert_eq!(None, cache.expires());
+
+        let expires = Utc.with_ymd_and_hms(2100, 1, 1, 0, 0, 0).single();
+        cache.jwks = CacheControl {
+            expires,
+            ..Default::default()
+        };
+        assert_eq!(expires, cache.expires());
+
+        cache.config.expires = Utc.with_ymd_and_hms(2099, 1, 1, 0, 0, 0).single();
+        assert_eq!(cache.config.expires, cache.expires());
+    }
 }
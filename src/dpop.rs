@@ -0,0 +1,202 @@
+/*!
+DPoP proof-of-possession.
+
+A client with a `DPoPKey` sends a proof JWT signed with it in the `DPoP` header of token,
+refresh and userinfo requests, so the provider binds the issued tokens to the key and a leaked
+token cannot be replayed without it.
+
+See [RFC 9449](https://tools.ietf.org/html/rfc9449).
+*/
+use crate::error::Jose;
+use biscuit::{
+    jwa::SignatureAlgorithm,
+    jws::{Compact, Header, RegisteredHeader, Secret},
+    CompactJson,
+};
+use chrono::Utc;
+use http::{header::HeaderName, Method};
+use ring::{
+    digest::{digest, SHA256},
+    rand::{SecureRandom, SystemRandom},
+    signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING},
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc};
+use url::Url;
+
+/// The `DPoP` request header carrying the proof.
+pub const DPOP: HeaderName = HeaderName::from_static("dpop");
+
+/// The `DPoP-Nonce` response header carrying a server provided nonce.
+pub const DPOP_NONCE: HeaderName = HeaderName::from_static("dpop-nonce");
+
+/// The public part of a P-256 key, as sent in the proof header.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+struct PublicJwk {
+    kty: String,
+    crv: String,
+    x: String,
+    y: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct ProofHeader {
+    jwk: PublicJwk,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct ProofClaims {
+    jti: String,
+    htm: String,
+    htu: String,
+    iat: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ath: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    nonce: Option<String>,
+}
+
+impl CompactJson for ProofClaims {}
+
+/// An ES256 key pair proving possession of DPoP bound tokens.
+#[derive(Clone)]
+pub struct DPoPKey {
+    key_pair: Arc<EcdsaKeyPair>,
+    jwk: PublicJwk,
+}
+
+impl DPoPKey {
+    /// Generates a new P-256 key pair. Keep the `pkcs8` document if tokens must stay usable
+    /// across restarts.
+    pub fn generate() -> Result<(Self, Vec<u8>), Jose> {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng)?;
+        let key = Self::from_pkcs8(pkcs8.as_ref())?;
+        Ok((key, pkcs8.as_ref().to_vec()))
+    }
+
+    /// Loads a PKCS#8 DER encoded P-256 private key.
+    pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self, Jose> {
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8)?;
+        // Uncompressed point: 0x04 || x || y
+        let point = key_pair.public_key().as_ref();
+        let jwk = PublicJwk {
+            kty: String::from("EC"),
+            crv: String::from("P-256"),
+            x: encode(&point[1..33]),
+            y: encode(&point[33..65]),
+        };
+        Ok(Self {
+            key_pair: Arc::new(key_pair),
+            jwk,
+        })
+    }
+
+    /// The JWK SHA-256 thumbprint of the public key, the `jkt` the provider binds tokens to.
+    ///
+    /// See [RFC 7638](https://tools.ietf.org/html/rfc7638).
+    pub fn thumbprint(&self) -> String {
+        // Required members in lexicographic order, without whitespace
+        let jwk = format!(
+            r#"{{"crv":"{}","kty":"{}","x":"{}","y":"{}"}}"#,
+            self.jwk.crv, self.jwk.kty, self.jwk.x, self.jwk.y
+        );
+        encode(digest(&SHA256, jwk.as_bytes()).as_ref())
+    }
+
+    /// Creates a proof for a request. Pass the access token when calling a protected resource
+    /// and the last nonce the server sent, if any.
+    ///
+    /// See [RFC 9449, section 4.2](https://tools.ietf.org/html/rfc9449#section-4.2).
+    pub fn proof(
+        &self,
+        method: &Method,
+        url: &Url,
+        access_token: Option<&str>,
+        nonce: Option<&str>,
+    ) -> Result<String, Jose> {
+        let mut jti = [0; 16];
+        SystemRandom::new().fill(&mut jti)?;
+
+        let mut htu = url.clone();
+        htu.set_query(None);
+        htu.set_fragment(None);
+
+        let header = Header {
+            registered: RegisteredHeader {
+                algorithm: SignatureAlgorithm::ES256,
+                media_type: Some(String::from("dpop+jwt")),
+                ..Default::default()
+            },
+            private: ProofHeader {
+                jwk: self.jwk.clone(),
+            },
+        };
+        let claims = ProofClaims {
+            jti: encode(&jti),
+            htm: method.as_str().to_string(),
+            htu: String::from(htu),
+            iat: Utc::now().timestamp(),
+            ath: access_token.map(|token| encode(digest(&SHA256, token.as_bytes()).as_ref())),
+            nonce: nonce.map(str::to_string),
+        };
+        let secret = Secret::EcdsaKeyPair(self.key_pair.clone());
+        let proof = Compact::new_decoded(header, claims).encode(&secret)?;
+        Ok(proof.encoded()?.to_string())
+    }
+}
+
+impl fmt::Debug for DPoPKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DPoPKey").field("jwk", &self.jwk).finish()
+    }
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DPoPKey;
+    use http::Method;
+    use ring::signature::{KeyPair, UnparsedPublicKey, ECDSA_P256_SHA256_FIXED};
+    use serde_json::Value;
+    use url::Url;
+
+    fn decode(part: &str) -> Vec<u8> {
+        base64::decode_config(part, base64::URL_SAFE_NO_PAD).unwrap()
+    }
+
+    #[test]
+    fn proof() {
+        let (key, pkcs8) = DPoPKey::generate().unwrap();
+        assert_eq!(
+            key.thumbprint(),
+            DPoPKey::from_pkcs8(&pkcs8).unwrap().thumbprint()
+        );
+
+        let url = Url::parse("https://example.com/token?query#fragment").unwrap();
+        let proof = key
+            .proof(&Method::POST, &url, Some("token"), Some("nonce"))
+            .unwrap();
+        let parts: Vec<&str> = proof.split('.').collect();
+
+        let public_key = key.key_pair.public_key().as_ref();
+        let signed = format!("{}.{}", parts[0], parts[1]);
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, public_key)
+            .verify(signed.as_bytes(), &decode(parts[2]))
+            .unwrap();
+
+        let header: Value = serde_json::from_slice(&decode(parts[0])).unwrap();
+        assert_eq!("ES256", header["alg"]);
+        assert_eq!("dpop+jwt", header["typ"]);
+        assert_eq!(key.jwk.x, header["jwk"]["x"]);
+        let claims: Value = serde_json::from_slice(&decode(parts[1])).unwrap();
+        assert_eq!("POST", claims["htm"]);
+        assert_eq!("https://example.com/token", claims["htu"]);
+        assert_eq!("nonce", claims["nonce"]);
+        // SHA-256 of "token"
+        assert_eq!("PEaenWxYddN6Q_NT1PiOYfz4EsZu7jRXRlpAsNpBU-A", claims["ath"]);
+    }
+}
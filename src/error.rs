@@ -42,6 +42,7 @@ impl error::Error for OAuth2Error {
 ///
 /// See [RFC 6749, section 5.2](http://tools.ietf.org/html/rfc6749#section-5.2).
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
 pub enum OAuth2ErrorCode {
     /// The request is missing a required parameter, includes an unsupported parameter value (other
     /// than grant type), repeats a parameter, includes multiple credentials, utilizes more than
@@ -84,6 +85,12 @@ impl<'a> From<&'a str> for OAuth2ErrorCode {
         }
     }
 }
+
+impl From<String> for OAuth2ErrorCode {
+    fn from(s: String) -> OAuth2ErrorCode {
+        s.as_str().into()
+    }
+}
 #[derive(Debug)]
 pub enum ClientError {
     /// IO error.
@@ -92,12 +99,15 @@ pub enum ClientError {
     /// URL error.
     Url(url::ParseError),
 
-    /// Reqwest error.
-    Reqwest(reqwest::Error),
+    /// HTTP transport error.
+    Http(Http),
 
     /// JSON error.
     Json(serde_json::Error),
 
+    /// Error signing a DPoP proof.
+    Jose(Jose),
+
     /// Response parse error.
     //    Parse(ParseError),
 
@@ -109,8 +119,9 @@ impl fmt::Display for ClientError {
         match *self {
             ClientError::Io(ref err) => write!(f, "{}", err),
             ClientError::Url(ref err) => write!(f, "{}", err),
-            ClientError::Reqwest(ref err) => write!(f, "{}", err),
+            ClientError::Http(ref err) => write!(f, "{}", err),
             ClientError::Json(ref err) => write!(f, "{}", err),
+            ClientError::Jose(ref err) => write!(f, "{}", err),
             // ClientError::Parse(ref err) => write!(f, "{}", err),
             ClientError::OAuth2(ref err) => write!(f, "{}", err),
         }
@@ -122,8 +133,9 @@ impl std::error::Error for ClientError {
         match *self {
             ClientError::Io(ref err) => Some(err),
             ClientError::Url(ref err) => Some(err),
-            ClientError::Reqwest(ref err) => Some(err),
+            ClientError::Http(ref err) => Some(err),
             ClientError::Json(ref err) => Some(err),
+            ClientError::Jose(ref err) => Some(err),
             // ClientError::Parse(ref err) => Some(err),
             ClientError::OAuth2(ref err) => Some(err),
         }
@@ -142,18 +154,48 @@ macro_rules! impl_from {
 
 impl_from!(ClientError::Io, std::io::Error);
 impl_from!(ClientError::Url, url::ParseError);
-impl_from!(ClientError::Reqwest, reqwest::Error);
+impl_from!(ClientError::Http, Http);
 impl_from!(ClientError::Json, serde_json::Error);
+impl_from!(ClientError::Jose, Jose);
 // impl_from!(ClientError::Parse, ParseError);
 impl_from!(ClientError::OAuth2, OAuth2Error);
 
 pub use biscuit::errors::Error as Jose;
-pub use reqwest::Error as Http;
 // pub use reqwest::UrlError as Url;
 pub use serde_json::Error as Json;
 
 use failure::Fail;
 
+/// HTTP transport error, as returned by an [`HttpClient`](../http_client/trait.HttpClient.html).
+#[derive(Debug)]
+pub struct Http(Box<dyn error::Error + Send + Sync>);
+
+impl Http {
+    /// Wraps the error of an `HttpClient` implementation.
+    pub fn new<E: Into<Box<dyn error::Error + Send + Sync>>>(err: E) -> Self {
+        Http(err.into())
+    }
+}
+
+impl fmt::Display for Http {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl error::Error for Http {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&*self.0)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Error> for Http {
+    fn from(err: reqwest::Error) -> Self {
+        Http::new(err)
+    }
+}
+
 #[derive(Debug, Fail)]
 pub enum Error {
     #[fail(display = "{}", _0)]
@@ -172,12 +214,38 @@ pub enum Error {
     Validation(#[fail(cause)] Validation),
     #[fail(display = "{}", _0)]
     Userinfo(#[fail(cause)] Userinfo),
+    #[fail(display = "{}", _0)]
+    Introspection(#[fail(cause)] Introspection),
+    #[fail(display = "{}", _0)]
+    Discovery(#[fail(cause)] Discovery),
+    #[fail(display = "{}", _0)]
+    Webfinger(#[fail(cause)] Webfinger),
+    #[fail(display = "{}", _0)]
+    Backchannel(#[fail(cause)] Backchannel),
+    #[fail(display = "{}", _0)]
+    ClaimSource(#[fail(cause)] ClaimSource),
     #[fail(display = "Url must use TLS: '{}'", _0)]
-    Insecure(::reqwest::Url),
+    Insecure(::url::Url),
     #[fail(display = "Scope must contain Openid")]
     MissingOpenidScope,
     #[fail(display = "Url: Path segments is cannot-be-a-base")]
     CannotBeABase,
+    #[fail(display = "Provider does not support response_mode: '{}'", _0)]
+    UnsupportedResponseMode(String),
+    #[fail(display = "Authorization response has no code")]
+    MissingCode,
+    #[fail(
+        display = "Resource must be an absolute URI without fragment: '{}'",
+        _0
+    )]
+    InvalidResource(String),
+    #[fail(
+        display = "Provider does not support authorization details type: '{}'",
+        _0
+    )]
+    UnsupportedAuthorizationDetailsType(String),
+    #[fail(display = "Provider does not support the claims request parameter")]
+    UnsupportedClaimsParameter,
     #[fail(display = "{}", _0)]
     ClientError(#[fail(cause)] ClientError),
 }
@@ -201,6 +269,11 @@ from!(ClientError);
 from!(Decode);
 from!(Validation);
 from!(Userinfo);
+from!(Introspection);
+from!(Discovery);
+from!(Webfinger);
+from!(Backchannel);
+from!(ClaimSource);
 
 #[derive(Debug, Fail)]
 pub enum Decode {
@@ -210,6 +283,14 @@ pub enum Decode {
     MissingKey(String),
     #[fail(display = "JWK Set is empty")]
     EmptySet,
+    #[fail(display = "JWK Set has no key for verifying signatures")]
+    NoSigningKey,
+    #[fail(display = "Signatures of this key type cannot be verified: {}", _0)]
+    UnsupportedKey(String),
+    #[fail(display = "Token is not signed")]
+    Unsigned,
+    #[fail(display = "Token signature algorithm is not allowed: {}", _0)]
+    DisallowedAlgorithm(String),
 }
 
 #[derive(Debug, Fail)]
@@ -220,6 +301,8 @@ pub enum Validation {
     Missing(#[fail(cause)] Missing),
     #[fail(display = "{}", _0)]
     Expired(#[fail(cause)] Expiry),
+    #[fail(display = "{}", _0)]
+    InsufficientAuthentication(#[fail(cause)] InsufficientAuthentication),
 }
 
 #[derive(Debug, Fail)]
@@ -234,11 +317,45 @@ pub enum Mismatch {
         expected, actual
     )]
     Issuer { expected: String, actual: String },
+    #[fail(
+        display = "Trusted audiences and token audience mismatch: '{}', '{}'",
+        expected, actual
+    )]
+    Audience { expected: String, actual: String },
+    #[fail(
+        display = "Allowed algorithms and token algorithm mismatch: '{}', '{}'",
+        expected, actual
+    )]
+    Algorithm { expected: String, actual: String },
     #[fail(
         display = "Given nonce does not match token nonce: '{}', '{}'",
         expected, actual
     )]
     Nonce { expected: String, actual: String },
+    #[fail(
+        display = "Client certificate and token confirmation thumbprint mismatch: '{}', '{}'",
+        expected, actual
+    )]
+    CertificateThumbprint { expected: String, actual: String },
+    #[fail(
+        display = "Expected token type and token typ header mismatch: '{}', '{}'",
+        expected, actual
+    )]
+    TokenType { expected: String, actual: String },
+    #[fail(
+        display = "Allowed clients and token client ID mismatch: '{}', '{}'",
+        expected, actual
+    )]
+    ClientId { expected: String, actual: String },
+    #[fail(
+        display = "Requested and returned value of claim '{}' mismatch: {}, {}",
+        name, expected, actual
+    )]
+    Claim {
+        name: String,
+        expected: String,
+        actual: String,
+    },
 }
 
 #[derive(Debug, Fail)]
@@ -251,6 +368,28 @@ pub enum Missing {
     AuthTime,
     #[fail(display = "Token missing Nonce")]
     Nonce,
+    #[fail(display = "Token missing certificate confirmation thumbprint")]
+    CertificateThumbprint,
+    #[fail(display = "Token missing scope: {}", _0)]
+    Scope(String),
+    #[fail(display = "Token missing essential claim: {}", _0)]
+    Claim(String),
+    #[fail(display = "Token is DPoP bound, but the client has no DPoP key")]
+    DPoPKey,
+}
+
+#[derive(Debug, Fail)]
+pub enum InsufficientAuthentication {
+    #[fail(
+        display = "Token acr is not one of the required values: '{}', {:?}",
+        expected, actual
+    )]
+    Acr {
+        expected: String,
+        actual: Option<String>,
+    },
+    #[fail(display = "Token amr misses required method: {}", _0)]
+    Amr(String),
 }
 
 #[derive(Debug, Fail)]
@@ -259,6 +398,8 @@ pub enum Expiry {
     Expires(::chrono::naive::NaiveDateTime),
     #[fail(display = "Token is too old: {}", _0)]
     MaxAge(::chrono::Duration),
+    #[fail(display = "Token issued in the future: {}", _0)]
+    NotYetValid(::chrono::naive::NaiveDateTime),
 }
 
 #[derive(Debug, Fail)]
@@ -271,3 +412,62 @@ pub enum Userinfo {
     )]
     MismatchSubject { expected: String, actual: String },
 }
+
+#[derive(Debug, Fail)]
+pub enum Introspection {
+    #[fail(display = "Config has no introspection url")]
+    NoUrl,
+}
+
+#[derive(Debug, Fail)]
+pub enum Discovery {
+    #[fail(
+        display = "Requested issuer and discovered issuer mismatch: '{}', '{}'",
+        expected, actual
+    )]
+    IssuerMismatch { expected: String, actual: String },
+    #[fail(display = "Discovered config does not support RS256 ID token signing")]
+    MissingRs256,
+    #[fail(display = "No discovery modes configured")]
+    NoModes,
+}
+
+#[derive(Debug, Fail)]
+pub enum Webfinger {
+    #[fail(display = "Not a valid WebFinger identifier: '{}'", _0)]
+    InvalidIdentifier(String),
+    #[fail(display = "WebFinger response has no issuer link")]
+    NoIssuer,
+}
+
+#[derive(Debug, Fail)]
+pub enum Backchannel {
+    #[fail(display = "Config has no backchannel authentication url")]
+    NoUrl,
+    #[fail(display = "Notification does not carry the client notification token")]
+    Unauthorized,
+    #[fail(display = "Notification has no auth_req_id or tokens")]
+    NoToken,
+    #[fail(display = "Pushed tokens have no ID token")]
+    NoIdToken,
+    #[fail(
+        display = "Request and ID token auth_req_id mismatch: '{}', '{}'",
+        expected, actual
+    )]
+    AuthReqIdMismatch { expected: String, actual: String },
+    #[fail(display = "ID token {} does not match the pushed token", _0)]
+    TokenHashMismatch(&'static str),
+}
+
+#[derive(Debug, Fail)]
+pub enum ClaimSource {
+    #[fail(
+        display = "Claim names refer to a source not in the claim sources: '{}'",
+        _0
+    )]
+    UnknownSource(String),
+    #[fail(display = "Claim source JWT is not from a trusted issuer: '{}'", _0)]
+    UntrustedIssuer(String),
+    #[fail(display = "Claim source '{}' does not have claim '{}'", source, claim)]
+    MissingClaim { source: String, claim: String },
+}
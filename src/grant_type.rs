@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+/// OAuth 2.0 grant types, as advertised in the Discovery config `grant_types_supported`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum GrantType {
+    /// See [RFC 6749, section 4.1](http://tools.ietf.org/html/rfc6749#section-4.1).
+    AuthorizationCode,
+    /// See [RFC 6749, section 4.2](http://tools.ietf.org/html/rfc6749#section-4.2).
+    Implicit,
+    /// See [RFC 6749, section 4.3](http://tools.ietf.org/html/rfc6749#section-4.3).
+    Password,
+    /// See [RFC 6749, section 4.4](http://tools.ietf.org/html/rfc6749#section-4.4).
+    ClientCredentials,
+    /// See [RFC 6749, section 6](http://tools.ietf.org/html/rfc6749#section-6).
+    RefreshToken,
+    /// See [RFC 8628](https://tools.ietf.org/html/rfc8628).
+    DeviceCode,
+    /// See [RFC 7523](https://tools.ietf.org/html/rfc7523).
+    JwtBearer,
+    /// See [RFC 8693](https://tools.ietf.org/html/rfc8693).
+    TokenExchange,
+    /// See [OpenID Connect CIBA](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html).
+    Ciba,
+    /// A grant type not listed above.
+    Other(String),
+}
+
+impl GrantType {
+    pub fn as_str(&self) -> &str {
+        use GrantType::*;
+        match *self {
+            AuthorizationCode => "authorization_code",
+            Implicit => "implicit",
+            Password => "password",
+            ClientCredentials => "client_credentials",
+            RefreshToken => "refresh_token",
+            DeviceCode => "urn:ietf:params:oauth:grant-type:device_code",
+            JwtBearer => "urn:ietf:params:oauth:grant-type:jwt-bearer",
+            TokenExchange => "urn:ietf:params:oauth:grant-type:token-exchange",
+            Ciba => "urn:openid:params:grant-type:ciba",
+            Other(ref s) => s.as_str(),
+        }
+    }
+}
+
+impl From<&str> for GrantType {
+    fn from(s: &str) -> Self {
+        match s {
+            "authorization_code" => GrantType::AuthorizationCode,
+            "implicit" => GrantType::Implicit,
+            "password" => GrantType::Password,
+            "client_credentials" => GrantType::ClientCredentials,
+            "refresh_token" => GrantType::RefreshToken,
+            "urn:ietf:params:oauth:grant-type:device_code" => GrantType::DeviceCode,
+            "urn:ietf:params:oauth:grant-type:jwt-bearer" => GrantType::JwtBearer,
+            "urn:ietf:params:oauth:grant-type:token-exchange" => GrantType::TokenExchange,
+            "urn:openid:params:grant-type:ciba" => GrantType::Ciba,
+            s => GrantType::Other(s.to_owned()),
+        }
+    }
+}
+
+impl From<String> for GrantType {
+    fn from(s: String) -> Self {
+        s.as_str().into()
+    }
+}
+
+impl From<GrantType> for String {
+    fn from(grant: GrantType) -> Self {
+        grant.as_str().to_owned()
+    }
+}
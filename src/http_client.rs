@@ -0,0 +1,257 @@
+/*!
+Pluggable HTTP transport.
+
+Every request this crate makes goes through an [`HttpClient`](trait.HttpClient.html). With the
+default `reqwest` feature it is implemented for `reqwest::Client`; implement it yourself to use
+another HTTP stack, a client with custom proxies or TLS settings, or a mock in tests.
+
+The TLS backend of reqwest is chosen with the `default-tls` (enabled by default), `native-tls`
+and `rustls-tls` features. For musl static builds without OpenSSL, disable default features and
+enable `reqwest` and `rustls-tls`.
+*/
+use crate::error::Http;
+use async_trait::async_trait;
+use http::{
+    header::{HeaderName, HeaderValue, AUTHORIZATION},
+    HeaderMap, Method, StatusCode,
+};
+use std::fmt;
+use url::Url;
+
+/// An HTTP request built by this crate.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// A GET request without headers.
+    pub fn get(url: Url) -> Self {
+        Self {
+            method: Method::GET,
+            url,
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// A POST request with the given body.
+    pub fn post(url: Url, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            method: Method::POST,
+            url,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Adds a header. Errors if the value is not a valid header value, e.g. because it contains
+    /// a line break.
+    pub fn header(mut self, name: HeaderName, value: &str) -> Result<Self, Http> {
+        let value = HeaderValue::from_str(value).map_err(Http::new)?;
+        self.headers.append(name, value);
+        Ok(self)
+    }
+
+    /// Adds an `Authorization: Bearer` header.
+    pub fn bearer_auth(self, token: &str) -> Result<Self, Http> {
+        self.header(AUTHORIZATION, &format!("Bearer {}", token))
+    }
+
+    /// Adds an `Authorization: Basic` header.
+    ///
+    /// See [RFC 6749, section 2.3.1](http://tools.ietf.org/html/rfc6749#section-2.3.1).
+    pub fn basic_auth(self, username: &str, password: &str) -> Result<Self, Http> {
+        let credentials = base64::encode(format!("{}:{}", username, password));
+        self.header(AUTHORIZATION, &format!("Basic {}", credentials))
+    }
+}
+
+/// An HTTP response as returned by an `HttpClient`.
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Turns a 4xx or 5xx status into an error.
+    pub fn error_for_status(self) -> Result<Self, Http> {
+        if self.status.is_client_error() || self.status.is_server_error() {
+            Err(Http::new(StatusError(self.status)))
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Deserializes the JSON body.
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+#[derive(Debug)]
+struct StatusError(StatusCode);
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HTTP status {}", self.0)
+    }
+}
+
+impl std::error::Error for StatusError {}
+
+/// Sends HTTP requests for the client.
+#[async_trait]
+pub trait HttpClient: fmt::Debug + Send + Sync {
+    /// Sends the request and reads the whole response. Only transport failures are errors, an
+    /// error status is a normal response.
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Http>;
+}
+
+#[cfg(feature = "reqwest")]
+#[async_trait]
+impl HttpClient for reqwest::Client {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Http> {
+        let resp = self
+            .request(request.method, request.url)
+            .headers(request.headers)
+            .body(request.body)
+            .send()
+            .await?;
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?.to_vec();
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// Builds a reqwest client that also trusts the given PEM encoded root certificates, for
+/// providers with certificates from a private CA. Pass it to `Client::discover_with_client`.
+#[cfg(any(
+    feature = "default-tls",
+    feature = "native-tls",
+    feature = "rustls-tls"
+))]
+pub fn reqwest_with_root_certificates(certificates: &[&[u8]]) -> Result<reqwest::Client, Http> {
+    let mut builder = reqwest::Client::builder();
+    for certificate in certificates {
+        builder = builder.add_root_certificate(reqwest::Certificate::from_pem(certificate)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Blocking version of `reqwest_with_root_certificates`, for the `blocking` client.
+#[cfg(all(
+    feature = "blocking",
+    any(
+        feature = "default-tls",
+        feature = "native-tls",
+        feature = "rustls-tls"
+    )
+))]
+pub fn reqwest_blocking_with_root_certificates(
+    certificates: &[&[u8]],
+) -> Result<reqwest::blocking::Client, Http> {
+    let mut builder = reqwest::blocking::Client::builder();
+    for certificate in certificates {
+        builder = builder.add_root_certificate(reqwest::Certificate::from_pem(certificate)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Lets the clients hold transports that were boxed before they got there.
+#[async_trait]
+impl HttpClient for Box<dyn HttpClient> {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Http> {
+        (**self).send(request).await
+    }
+}
+
+/// Canned responses by url, for tests.
+#[cfg(test)]
+pub(crate) mod mock {
+    use super::{HttpClient, HttpRequest, HttpResponse};
+    use crate::error::Http;
+    use async_trait::async_trait;
+    use http::{HeaderMap, StatusCode};
+    use std::{collections::HashMap, sync::Arc, sync::Mutex};
+
+    #[derive(Debug, Default)]
+    pub struct MockHttpClient {
+        /// Responses are returned in order, the last one repeatedly.
+        responses: Mutex<HashMap<String, Vec<HttpResponse>>>,
+        pub requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    impl MockHttpClient {
+        pub fn with_json(self, url: &str, json: &str) -> Self {
+            let response = HttpResponse {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: json.as_bytes().to_vec(),
+            };
+            self.with_response(url, response)
+        }
+
+        pub fn with_response(self, url: &str, response: HttpResponse) -> Self {
+            self.responses
+                .lock()
+                .unwrap()
+                .entry(url.to_string())
+                .or_default()
+                .push(response);
+            self
+        }
+    }
+
+    #[async_trait]
+    impl HttpClient for MockHttpClient {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Http> {
+            let url = request.url.to_string();
+            self.requests.lock().unwrap().push(request);
+            let mut responses = self.responses.lock().unwrap();
+            Ok(match responses.get_mut(&url) {
+                Some(responses) if responses.len() > 1 => responses.remove(0),
+                Some(responses) => responses[0].clone(),
+                None => HttpResponse {
+                    status: StatusCode::NOT_FOUND,
+                    headers: HeaderMap::new(),
+                    body: Vec::new(),
+                },
+            })
+        }
+    }
+
+    /// Lets a test keep a handle on the requests a client sent.
+    #[async_trait]
+    impl HttpClient for Arc<MockHttpClient> {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Http> {
+            self.as_ref().send(request).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HttpRequest;
+    use http::header::AUTHORIZATION;
+    use url::Url;
+
+    #[test]
+    fn invalid_header_value() {
+        let url = Url::parse("https://example.com/userinfo").unwrap();
+        let request = HttpRequest::get(url.clone()).bearer_auth("token").unwrap();
+        assert_eq!("Bearer token", request.headers[AUTHORIZATION]);
+
+        assert!(HttpRequest::get(url).bearer_auth("to\r\nken").is_err());
+    }
+}
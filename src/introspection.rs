@@ -0,0 +1,46 @@
+use crate::{confirmation, error::Error, Confirmation};
+use biscuit::SingleOrMultiple;
+use serde::{Deserialize, Serialize};
+
+/// Token introspection response.
+///
+/// See [RFC 7662, section 2.2](https://tools.ietf.org/html/rfc7662#section-2.2).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Introspection {
+    /// Whether the token is currently active. If false, no other field should be relied upon.
+    pub active: bool,
+    #[serde(default)]
+    pub scope: Option<String>,
+    #[serde(default)]
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub token_type: Option<String>,
+    #[serde(default)]
+    pub exp: Option<i64>,
+    #[serde(default)]
+    pub iat: Option<i64>,
+    #[serde(default)]
+    pub nbf: Option<i64>,
+    #[serde(default)]
+    pub sub: Option<String>,
+    #[serde(default)]
+    pub aud: Option<SingleOrMultiple<String>>,
+    #[serde(default)]
+    pub iss: Option<String>,
+    #[serde(default)]
+    pub jti: Option<String>,
+    #[serde(default)]
+    pub cnf: Option<Confirmation>,
+}
+
+impl Introspection {
+    /// Checks the token is bound to the given DER encoded client certificate, the one presented
+    /// in the mutual TLS connection the token was used on.
+    ///
+    /// See [RFC 8705, section 3.2](https://tools.ietf.org/html/rfc8705#section-3.2).
+    pub fn verify_certificate(&self, certificate: &[u8]) -> Result<(), Error> {
+        confirmation::verify_certificate(self.cnf.as_ref(), certificate)
+    }
+}
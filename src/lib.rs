@@ -11,7 +11,7 @@ Implements [OpenID Connect Core 1.0](https://openid.net/specs/openid-connect-cor
 
 This is quick and dirty rewrite of [inth-oauth2](https://crates.io/crates/inth-oauth2) and [oidc](https://crates.io/crates/oidc) to use async / await. Basic idea was to solve particular task, as result most of good ideas from original crates were perverted and over-simplified.
 
-Using [reqwest](https://crates.io/crates/reqwest) for the HTTP client and [biscuit](https://crates.io/crates/biscuit) for Javascript Object Signing and Encryption (JOSE).
+Using [reqwest](https://crates.io/crates/reqwest) for the HTTP client and [biscuit](https://crates.io/crates/biscuit) for Javascript Object Signing and Encryption (JOSE). The HTTP client is pluggable: disable the default `reqwest` feature and implement `openid::HttpClient` to use another one.
 
 ## Usage
 
@@ -22,6 +22,13 @@ Add dependency to Cargo.toml:
 openid = "0.3"
 ```
 
+By default reqwest is used with its default TLS backend. To use rustls instead, for example for musl static builds without OpenSSL:
+
+```toml
+[dependencies]
+openid = { version = "0.3", default-features = false, features = ["reqwest", "rustls-tls"] }
+```
+
 ### Use case: [Actix](https://actix.rs/) web server with [JHipster](https://www.jhipster.tech/) generated frontend and [Google OpenID Connect](https://developers.google.com/identity/protocols/OpenIDConnect)
 
 This example provides only Rust part, assuming just default JHipster frontend settings.
@@ -168,7 +175,7 @@ async fn request_token(
     let mut token: Token = oidc_client.request_token(&query.code).await?.into();
     if let Some(mut id_token) = token.id_token.as_mut() {
         oidc_client.decode_token(&mut id_token)?;
-        oidc_client.validate_token(&id_token, None, None)?;
+        oidc_client.validate_token(&id_token, None)?;
         eprintln!("token: {:?}", id_token);
     } else {
         return Ok(None);
@@ -319,39 +326,88 @@ See full example: [openid-example](https://github.com/kilork/openid-example)
 #[macro_use]
 extern crate lazy_static;
 
+mod access_token;
 mod address;
+mod assertion_key;
+mod authorization_detail;
+mod authorization_response;
+pub mod backchannel;
 mod bearer;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod cache;
+mod claim_source;
 mod claims;
+mod claims_request;
 mod client;
+mod client_auth;
 mod config;
+mod confirmation;
 mod custom_claims;
 mod discovered;
 mod display;
+pub mod dpop;
 pub mod error;
+mod grant_type;
+pub mod http_client;
+mod introspection;
+mod localized_claims;
+mod metadata;
 mod options;
 mod prompt;
 pub mod provider;
+mod response_mode;
+mod response_type;
 mod standard_claims;
+pub mod step_up;
+mod subject_type;
 mod token;
+mod token_delivery_mode;
+mod token_exchange;
+mod token_type;
 mod userinfo;
+mod validation_options;
+pub mod webfinger;
 
 pub use ::biscuit::jws::Compact as Jws;
 pub use ::biscuit::{Compact, CompactJson, Empty, SingleOrMultiple};
+pub use access_token::{AccessTokenClaims, AccessTokenValidator};
 pub use address::Address;
+pub use assertion_key::AssertionKey;
+pub use authorization_detail::AuthorizationDetail;
+pub use authorization_response::AuthorizationResponse;
 pub use bearer::Bearer;
+pub use cache::CacheControl;
+pub use claim_source::{ClaimSource, ClaimsResolver};
 pub use claims::Claims;
+pub use claims_request::{ClaimRequest, ClaimsRequest};
 pub use client::Client;
-pub use config::Config;
+pub use client_auth::ClientAuthMethod;
+pub use config::{Config, MtlsEndpointAliases};
+pub use confirmation::{certificate_thumbprint, Confirmation};
 pub use custom_claims::CustomClaims;
-pub use discovered::Discovered;
+pub use discovered::{Discovered, DiscoveryMode, DiscoveryOptions};
 pub use display::Display;
+pub use dpop::DPoPKey;
 pub use error::{OAuth2Error, OAuth2ErrorCode};
+pub use grant_type::GrantType;
+pub use http_client::HttpClient;
+pub use introspection::Introspection;
+pub use localized_claims::LocalizedClaims;
+pub use metadata::Metadata;
 pub use options::Options;
 pub use prompt::Prompt;
 pub use provider::Provider;
+pub use response_mode::ResponseMode;
+pub use response_type::ResponseType;
 pub use standard_claims::StandardClaims;
+pub use subject_type::SubjectType;
 pub use token::Token;
+pub use token_delivery_mode::TokenDeliveryMode;
+pub use token_exchange::TokenExchange;
+pub use token_type::TokenType;
 pub use userinfo::Userinfo;
+pub use validation_options::ValidationOptions;
 
 /// Reimport `biscuit` depdendency.
 pub mod biscuit {
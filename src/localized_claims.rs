@@ -0,0 +1,86 @@
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::Value;
+use std::{collections::BTreeMap, fmt};
+
+/// Language-tagged variants of string claims, such as `family_name#ja-Kana-JP`, by claim name
+/// and language tag. Other claims are ignored, so this can be flattened into a claims struct.
+///
+/// See [OpenID Connect Core, section 5.2](https://openid.net/specs/openid-connect-core-1_0.html#ClaimsLanguagesAndScripts).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocalizedClaims(pub BTreeMap<String, BTreeMap<String, String>>);
+
+impl LocalizedClaims {
+    /// The value of the claim in the language, falling back to less specific tags, e.g.
+    /// `ja-Kana-JP`, then `ja-Kana`, then `ja`. Tags are compared case-insensitively.
+    pub fn get(&self, name: &str, language: &str) -> Option<&str> {
+        let variants = self.0.get(name)?;
+        let mut language = language;
+        loop {
+            let found = variants
+                .iter()
+                .find(|(tag, _)| tag.eq_ignore_ascii_case(language));
+            if let Some((_, value)) = found {
+                return Some(value);
+            }
+            match language.rfind('-') {
+                Some(end) => language = &language[..end],
+                None => return None,
+            }
+        }
+    }
+
+    /// Adds a variant of the claim in the language.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        language: impl Into<String>,
+        value: impl Into<String>,
+    ) {
+        self.0
+            .entry(name.into())
+            .or_default()
+            .insert(language.into(), value.into());
+    }
+}
+
+impl Serialize for LocalizedClaims {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let entries = self.0.iter().flat_map(|(name, variants)| {
+            variants
+                .iter()
+                .map(move |(language, value)| (format!("{}#{}", name, language), value))
+        });
+        serializer.collect_map(entries)
+    }
+}
+
+impl<'de> Deserialize<'de> for LocalizedClaims {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LocalizedVisitor;
+
+        impl<'de> Visitor<'de> for LocalizedVisitor {
+            type Value = LocalizedClaims;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of claims")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut claims = LocalizedClaims::default();
+                while let Some((key, value)) = map.next_entry::<String, Value>()? {
+                    if let (Some((name, language)), Value::String(value)) =
+                        (key.split_once('#'), value)
+                    {
+                        claims.insert(name, language, value);
+                    }
+                }
+                Ok(claims)
+            }
+        }
+
+        deserializer.deserialize_map(LocalizedVisitor)
+    }
+}
@@ -0,0 +1,32 @@
+use crate::{Config, DiscoveryMode, DiscoveryOptions};
+use biscuit::{jwk::JWKSet, Empty};
+use serde::{Deserialize, Serialize};
+
+/// A snapshot of the discovered provider config and key set.
+///
+/// Persist it with `Client::metadata`, and load it with `Client::from_metadata` to boot and
+/// validate tokens while the provider is unreachable.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Metadata {
+    pub config: Config,
+    pub jwks: JWKSet<Empty>,
+    /// Where the config was discovered, so a refresh fetches it from the same place.
+    #[serde(default)]
+    pub mode: DiscoveryMode,
+    /// The options the config was discovered with, applied again on refresh.
+    #[serde(default)]
+    pub options: DiscoveryOptions,
+}
+
+impl Clone for Metadata {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            jwks: JWKSet {
+                keys: self.jwks.keys.clone(),
+            },
+            mode: self.mode,
+            options: self.options.clone(),
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use crate::{Display, Prompt};
+use crate::{
+    error::Error, AuthorizationDetail, ClaimsRequest, Config, Display, Prompt, ResponseMode,
+};
 use chrono::Duration;
 use std::collections::HashSet;
+use url::Url;
 
 /// Optional parameters that [OpenID specifies](https://openid.net/specs/openid-connect-basic-1_0.html#RequestParameters) for the auth URI.
 /// Derives Default, so remember to ..Default::default() after you specify what you want.
@@ -20,4 +23,57 @@ pub struct Options {
     pub id_token_hint: Option<String>,
     pub login_hint: Option<String>,
     pub acr_values: Option<String>,
+    /// How the authorization response is returned to the redirect URI. Use `FormPost` to keep
+    /// the code out of URLs, then parse the callback with
+    /// [`AuthorizationResponse::from_form_post`](struct.AuthorizationResponse.html#method.from_form_post).
+    pub response_mode: Option<ResponseMode>,
+    /// Absolute URIs of the APIs the token is for, sent as repeated `resource` parameters. Pass
+    /// the same ones to `request_token_with_resources`.
+    ///
+    /// See [RFC 8707](https://tools.ietf.org/html/rfc8707).
+    pub resource: Vec<String>,
+    /// Fine-grained permissions to ask for, sent as JSON.
+    ///
+    /// See [RFC 9396](https://tools.ietf.org/html/rfc9396).
+    pub authorization_details: Option<Vec<AuthorizationDetail>>,
+    /// Individual claims to ask for in the ID token and userinfo response, sent as JSON. Check
+    /// what came back with `ClaimsRequest::verify_id_token` and `verify_userinfo`.
+    pub claims: Option<ClaimsRequest>,
+}
+
+impl Options {
+    /// Checks these options against what the provider advertises in its discovery document.
+    /// Errors are:
+    ///
+    /// - Error::UnsupportedResponseMode if the response_mode is not in `response_modes_supported`
+    /// - Error::InvalidResource if a resource is not an absolute URI without fragment
+    /// - Error::UnsupportedAuthorizationDetailsType if a type is not in
+    ///   `authorization_details_types_supported`
+    /// - Error::UnsupportedClaimsParameter if claims are set and `claims_parameter_supported` is
+    ///   false
+    pub fn validate(&self, config: &Config) -> Result<(), Error> {
+        if let Some(ref mode) = self.response_mode {
+            if !config.supports_response_mode(mode.clone()) {
+                return Err(Error::UnsupportedResponseMode(mode.as_str().to_string()));
+            }
+        }
+        for resource in &self.resource {
+            match Url::parse(resource) {
+                Ok(ref url) if url.fragment().is_none() => {}
+                _ => return Err(Error::InvalidResource(resource.clone())),
+            }
+        }
+        if let Some(ref details) = self.authorization_details {
+            for detail in details {
+                if !config.supports_authorization_details_type(&detail.detail_type) {
+                    let detail_type = detail.detail_type.clone();
+                    return Err(Error::UnsupportedAuthorizationDetailsType(detail_type));
+                }
+            }
+        }
+        if self.claims.is_some() && !config.claims_parameter_supported {
+            return Err(Error::UnsupportedClaimsParameter);
+        }
+        Ok(())
+    }
 }
@@ -24,6 +24,13 @@ pub trait Provider {
     fn credentials_in_body(&self) -> bool {
         false
     }
+
+    /// The token endpoint URI to use when authenticating with mutual TLS, if different.
+    ///
+    /// See [RFC 8705, section 5](https://tools.ietf.org/html/rfc8705#section-5).
+    fn mtls_token_uri(&self) -> Option<&Url> {
+        None
+    }
 }
 
 /// Google OAuth 2.0 providers.
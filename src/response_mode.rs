@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+/// The possible values for the response_mode parameter set in Options, and advertised in the
+/// Discovery config `response_modes_supported`. See
+/// [OAuth 2.0 Multiple Response Type Encoding Practices](https://openid.net/specs/oauth-v2-multiple-response-types-1_0.html#ResponseModes)
+/// and [OAuth 2.0 Form Post Response Mode](https://openid.net/specs/oauth-v2-form-post-response-mode-1_0.html).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum ResponseMode {
+    Query,
+    Fragment,
+    FormPost,
+    /// A response mode not defined by the specs above.
+    Other(String),
+}
+
+impl ResponseMode {
+    pub fn as_str(&self) -> &str {
+        use ResponseMode::*;
+        match *self {
+            Query => "query",
+            Fragment => "fragment",
+            FormPost => "form_post",
+            Other(ref s) => s.as_str(),
+        }
+    }
+}
+
+impl From<&str> for ResponseMode {
+    fn from(s: &str) -> Self {
+        match s {
+            "query" => ResponseMode::Query,
+            "fragment" => ResponseMode::Fragment,
+            "form_post" => ResponseMode::FormPost,
+            s => ResponseMode::Other(s.to_owned()),
+        }
+    }
+}
+
+impl From<String> for ResponseMode {
+    fn from(s: String) -> Self {
+        s.as_str().into()
+    }
+}
+
+impl From<ResponseMode> for String {
+    fn from(mode: ResponseMode) -> Self {
+        mode.as_str().to_owned()
+    }
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// The response_type values advertised in the Discovery config `response_types_supported`.
+///
+/// There are only three basic response types plus their combinations, and none. The order of
+/// the space separated values does not matter, so `id_token code` is parsed as `CodeIdToken`.
+///
+/// See [OAuth 2.0 Multiple Response Type Encoding Practices](https://openid.net/specs/oauth-v2-multiple-response-types-1_0.html#Combinations).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum ResponseType {
+    Code,
+    IdToken,
+    Token,
+    CodeIdToken,
+    CodeToken,
+    IdTokenToken,
+    CodeIdTokenToken,
+    None,
+    /// A response type not defined by the specs above.
+    Other(String),
+}
+
+impl ResponseType {
+    pub fn as_str(&self) -> &str {
+        use ResponseType::*;
+        match *self {
+            Code => "code",
+            IdToken => "id_token",
+            Token => "token",
+            CodeIdToken => "code id_token",
+            CodeToken => "code token",
+            IdTokenToken => "id_token token",
+            CodeIdTokenToken => "code id_token token",
+            None => "none",
+            Other(ref s) => s.as_str(),
+        }
+    }
+}
+
+impl From<&str> for ResponseType {
+    fn from(s: &str) -> Self {
+        let values: BTreeSet<&str> = s.split_whitespace().collect();
+        let values: Vec<&str> = values.into_iter().collect();
+        match values.as_slice() {
+            ["code"] => ResponseType::Code,
+            ["id_token"] => ResponseType::IdToken,
+            ["token"] => ResponseType::Token,
+            ["code", "id_token"] => ResponseType::CodeIdToken,
+            ["code", "token"] => ResponseType::CodeToken,
+            ["id_token", "token"] => ResponseType::IdTokenToken,
+            ["code", "id_token", "token"] => ResponseType::CodeIdTokenToken,
+            ["none"] => ResponseType::None,
+            _ => ResponseType::Other(s.to_owned()),
+        }
+    }
+}
+
+impl From<String> for ResponseType {
+    fn from(s: String) -> Self {
+        s.as_str().into()
+    }
+}
+
+impl From<ResponseType> for String {
+    fn from(response_type: ResponseType) -> Self {
+        response_type.as_str().to_owned()
+    }
+}
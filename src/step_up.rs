@@ -0,0 +1,274 @@
+/*!
+Step-up authentication.
+
+A resource server that needs a stronger or more recent authentication than the access token
+carries answers with an `insufficient_user_authentication` challenge. Parse it with
+[`StepUpChallenge`](struct.StepUpChallenge.html), send the user through a new
+[`auth_url`](../struct.Client.html#method.auth_url) with the challenge applied to the options, and
+check the new ID token with [`AuthenticationRequirements`](struct.AuthenticationRequirements.html).
+
+See [RFC 9470](https://tools.ietf.org/html/rfc9470).
+*/
+use crate::{
+    error::{Error, InsufficientAuthentication, Validation},
+    http_client::HttpResponse,
+    Claims, Options,
+};
+use chrono::Duration;
+use http::header::WWW_AUTHENTICATE;
+
+/// How the user must have authenticated for an ID token to be accepted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuthenticationRequirements {
+    /// The `acr` of the token must be one of these. Empty accepts any.
+    pub acr_values: Vec<String>,
+    /// The `amr` of the token must contain all of these methods.
+    pub amr: Vec<String>,
+}
+
+impl AuthenticationRequirements {
+    /// Requires the `acr` of the token to be one of the values.
+    pub fn acr_values<S: Into<String>>(values: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            acr_values: values.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Checks the claims of an ID token against the requirements. Errors are:
+    ///
+    /// - Validation::InsufficientAuthentication::Acr if the acr is missing or not allowed
+    /// - Validation::InsufficientAuthentication::Amr if a required method is not in the amr
+    pub fn verify<C: Claims>(&self, claims: &C) -> Result<(), Error> {
+        if !self.acr_values.is_empty() {
+            let acr = claims.acr();
+            if !acr.is_some_and(|acr| self.acr_values.contains(acr)) {
+                return Err(Validation::InsufficientAuthentication(
+                    InsufficientAuthentication::Acr {
+                        expected: self.acr_values.join(" "),
+                        actual: acr.cloned(),
+                    },
+                )
+                .into());
+            }
+        }
+        let amr = claims.amr().map(Vec::as_slice).unwrap_or_default();
+        for method in &self.amr {
+            if !amr.contains(method) {
+                return Err(Validation::InsufficientAuthentication(
+                    InsufficientAuthentication::Amr(method.clone()),
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An `insufficient_user_authentication` challenge of a resource server.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StepUpChallenge {
+    /// Space separated acr values the resource server accepts.
+    pub acr_values: Option<String>,
+    /// How long ago the user may have authenticated at most.
+    pub max_age: Option<Duration>,
+    pub error_description: Option<String>,
+}
+
+impl StepUpChallenge {
+    /// Parses the challenge from the `WWW-Authenticate` headers of a response, None if there is
+    /// no `insufficient_user_authentication` error.
+    pub fn from_response(response: &HttpResponse) -> Option<Self> {
+        response
+            .headers
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .find_map(Self::parse)
+    }
+
+    /// Parses the challenge from a `WWW-Authenticate` header value, None if no challenge in it
+    /// has an `insufficient_user_authentication` error. The parameters of each challenge are
+    /// read separately, so those of other schemes in the same header are ignored.
+    pub fn parse(header: &str) -> Option<Self> {
+        challenges(header).into_iter().find_map(|(_, params)| {
+            let param = |name: &str| {
+                params
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                    .map(|(_, value)| value.clone())
+            };
+            if param("error")? != "insufficient_user_authentication" {
+                return None;
+            }
+            Some(Self {
+                acr_values: param("acr_values"),
+                max_age: param("max_age")
+                    .and_then(|max_age| max_age.parse().ok())
+                    .map(Duration::seconds),
+                error_description: param("error_description"),
+            })
+        })
+    }
+
+    /// Sets the acr values and max age of the challenge on the options of the next auth url.
+    pub fn apply(&self, options: &mut Options) {
+        if self.acr_values.is_some() {
+            options.acr_values = self.acr_values.clone();
+        }
+        if self.max_age.is_some() {
+            options.max_age = self.max_age;
+        }
+    }
+
+    /// What the new ID token must satisfy for the challenge.
+    pub fn requirements(&self) -> AuthenticationRequirements {
+        let acr_values = self.acr_values.as_deref().unwrap_or_default();
+        AuthenticationRequirements::acr_values(acr_values.split_whitespace())
+    }
+}
+
+/// An auth scheme and its `name=value` parameters.
+type Challenge = (String, Vec<(String, String)>);
+
+/// The challenges of a `WWW-Authenticate` header, with quoted values unescaped. Parameters
+/// belong to the scheme name before them.
+fn challenges(header: &str) -> Vec<Challenge> {
+    let mut challenges: Vec<Challenge> = Vec::new();
+    let mut chars = header.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| *c == ',' || c.is_whitespace()) {
+            chars.next();
+        }
+        let mut name = String::new();
+        while let Some(&c) = chars.peek() {
+            if c == '=' || c == ',' || c.is_whitespace() {
+                break;
+            }
+            name.push(c);
+            chars.next();
+        }
+        if name.is_empty() {
+            return challenges;
+        }
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        // A token without a value is a scheme name, starting the next challenge
+        if chars.peek() != Some(&'=') {
+            challenges.push((name, Vec::new()));
+            continue;
+        }
+        chars.next();
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        let mut value = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => value.extend(chars.next()),
+                    c => value.push(c),
+                }
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c == ',' || c.is_whitespace() {
+                    break;
+                }
+                value.push(c);
+                chars.next();
+            }
+        }
+        match challenges.last_mut() {
+            Some((_, params)) => params.push((name, value)),
+            None => challenges.push((String::new(), vec![(name, value)])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuthenticationRequirements, StepUpChallenge};
+    use crate::{
+        error::{Error, InsufficientAuthentication, Validation},
+        Options, StandardClaims,
+    };
+    use chrono::Duration;
+    use serde_json::json;
+
+    #[test]
+    fn parse_challenge() {
+        let header = r#"Bearer error="insufficient_user_authentication", error_description="A \"different\" authentication level is required", acr_values="myACR", max_age=5"#;
+        let challenge = StepUpChallenge::parse(header).unwrap();
+        assert_eq!(Some("myACR"), challenge.acr_values.as_deref());
+        assert_eq!(Some(Duration::seconds(5)), challenge.max_age);
+        assert_eq!(
+            Some(r#"A "different" authentication level is required"#),
+            challenge.error_description.as_deref()
+        );
+
+        let mut options = Options::default();
+        challenge.apply(&mut options);
+        assert_eq!(Some("myACR"), options.acr_values.as_deref());
+        assert_eq!(Some(Duration::seconds(5)), options.max_age);
+
+        assert_eq!(
+            None,
+            StepUpChallenge::parse(r#"Bearer error="invalid_token""#)
+        );
+        assert_eq!(None, StepUpChallenge::parse("Basic realm=\"example\""));
+    }
+
+    #[test]
+    fn parse_multiple_schemes() {
+        let header = r#"DPoP error="invalid_token", algs="ES256", Bearer error="insufficient_user_authentication", acr_values="myACR""#;
+        let challenge = StepUpChallenge::parse(header).unwrap();
+        assert_eq!(Some("myACR"), challenge.acr_values.as_deref());
+        assert_eq!(None, challenge.error_description);
+
+        // Parameters of the step-up challenge do not leak into another scheme's error
+        let header = r#"Bearer error="invalid_token", DPoP acr_values="myACR", error="insufficient_user_authentication""#;
+        let challenge = StepUpChallenge::parse(header).unwrap();
+        assert_eq!(Some("myACR"), challenge.acr_values.as_deref());
+        let header = r#"Bearer error="invalid_token", DPoP acr_values="myACR""#;
+        assert_eq!(None, StepUpChallenge::parse(header));
+    }
+
+    fn claims(acr: &str, amr: &[&str]) -> StandardClaims {
+        serde_json::from_value(json!({
+            "iss": "https://example.com/",
+            "sub": "248289761001",
+            "aud": "client",
+            "exp": 1311281970,
+            "iat": 1311280970,
+            "acr": acr,
+            "amr": amr
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn requirements() {
+        let mut requirements = AuthenticationRequirements::acr_values(vec!["gold", "silver"]);
+        requirements.amr = vec![String::from("mfa")];
+        requirements
+            .verify(&claims("silver", &["pwd", "mfa"]))
+            .unwrap();
+
+        match requirements.verify(&claims("bronze", &["mfa"])) {
+            Err(Error::Validation(Validation::InsufficientAuthentication(
+                InsufficientAuthentication::Acr { actual, .. },
+            ))) => assert_eq!(Some(String::from("bronze")), actual),
+            other => panic!("unexpected {:?}", other),
+        }
+        match requirements.verify(&claims("gold", &["pwd"])) {
+            Err(Error::Validation(Validation::InsufficientAuthentication(
+                InsufficientAuthentication::Amr(method),
+            ))) => assert_eq!("mfa", method),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// Subject identifier types, as advertised in the Discovery config `subject_types_supported`.
+///
+/// See [spec 8](https://openid.net/specs/openid-connect-core-1_0.html#SubjectIDTypes).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum SubjectType {
+    /// The same `sub` value is provided to all clients.
+    Public,
+    /// A different `sub` value is provided to each client.
+    Pairwise,
+    /// A subject type not defined by the spec.
+    Other(String),
+}
+
+impl SubjectType {
+    pub fn as_str(&self) -> &str {
+        use SubjectType::*;
+        match *self {
+            Public => "public",
+            Pairwise => "pairwise",
+            Other(ref s) => s.as_str(),
+        }
+    }
+}
+
+impl From<&str> for SubjectType {
+    fn from(s: &str) -> Self {
+        match s {
+            "public" => SubjectType::Public,
+            "pairwise" => SubjectType::Pairwise,
+            s => SubjectType::Other(s.to_owned()),
+        }
+    }
+}
+
+impl From<String> for SubjectType {
+    fn from(s: String) -> Self {
+        s.as_str().into()
+    }
+}
+
+impl From<SubjectType> for String {
+    fn from(subject_type: SubjectType) -> Self {
+        subject_type.as_str().to_owned()
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+/// How the provider delivers the tokens of a backchannel authentication, advertised in the
+/// Discovery config `backchannel_token_delivery_modes_supported`.
+///
+/// See [CIBA, section 5](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.5).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum TokenDeliveryMode {
+    /// The client polls the token endpoint.
+    Poll,
+    /// The provider calls the client notification endpoint, then the client gets the tokens from
+    /// the token endpoint.
+    Ping,
+    /// The provider sends the tokens to the client notification endpoint.
+    Push,
+    /// A delivery mode not listed above.
+    Other(String),
+}
+
+impl TokenDeliveryMode {
+    pub fn as_str(&self) -> &str {
+        use TokenDeliveryMode::*;
+        match *self {
+            Poll => "poll",
+            Ping => "ping",
+            Push => "push",
+            Other(ref s) => s.as_str(),
+        }
+    }
+}
+
+impl From<&str> for TokenDeliveryMode {
+    fn from(s: &str) -> Self {
+        match s {
+            "poll" => TokenDeliveryMode::Poll,
+            "ping" => TokenDeliveryMode::Ping,
+            "push" => TokenDeliveryMode::Push,
+            s => TokenDeliveryMode::Other(s.to_owned()),
+        }
+    }
+}
+
+impl From<String> for TokenDeliveryMode {
+    fn from(s: String) -> Self {
+        s.as_str().into()
+    }
+}
+
+impl From<TokenDeliveryMode> for String {
+    fn from(mode: TokenDeliveryMode) -> Self {
+        mode.as_str().to_owned()
+    }
+}
@@ -0,0 +1,40 @@
+use crate::TokenType;
+
+/// Parameters of a token exchange request. Start from `new` and set the optional fields you
+/// need.
+///
+/// See [RFC 8693, section 2.1](https://tools.ietf.org/html/rfc8693#section-2.1).
+#[derive(Debug, Clone)]
+pub struct TokenExchange {
+    /// The token representing the party on whose behalf the request is made.
+    pub subject_token: String,
+    pub subject_token_type: TokenType,
+    /// The token representing the acting party, for delegation.
+    pub actor_token: Option<(String, TokenType)>,
+    /// Logical names of the target services.
+    pub audience: Vec<String>,
+    /// URIs of the target services.
+    pub resource: Vec<String>,
+    pub scope: Option<String>,
+    /// The type of token wanted, the provider decides if not given.
+    pub requested_token_type: Option<TokenType>,
+}
+
+impl TokenExchange {
+    pub fn new(subject_token: impl Into<String>, subject_token_type: TokenType) -> Self {
+        Self {
+            subject_token: subject_token.into(),
+            subject_token_type,
+            actor_token: None,
+            audience: Vec::new(),
+            resource: Vec::new(),
+            scope: None,
+            requested_token_type: None,
+        }
+    }
+
+    /// Exchange an access token, the most common case.
+    pub fn access_token(subject_token: impl Into<String>) -> Self {
+        Self::new(subject_token, TokenType::AccessToken)
+    }
+}
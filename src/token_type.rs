@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+/// Token type identifiers, used by the token exchange grant to describe the tokens sent and
+/// issued.
+///
+/// See [RFC 8693, section 3](https://tools.ietf.org/html/rfc8693#section-3).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum TokenType {
+    /// An OAuth 2.0 access token.
+    AccessToken,
+    /// An OAuth 2.0 refresh token.
+    RefreshToken,
+    /// An OpenID Connect ID token.
+    IdToken,
+    /// A base64url encoded SAML 1.1 assertion.
+    Saml1,
+    /// A base64url encoded SAML 2.0 assertion.
+    Saml2,
+    /// A JWT, see [RFC 7519](https://tools.ietf.org/html/rfc7519).
+    Jwt,
+    /// A token type not listed above.
+    Other(String),
+}
+
+impl TokenType {
+    pub fn as_str(&self) -> &str {
+        use TokenType::*;
+        match *self {
+            AccessToken => "urn:ietf:params:oauth:token-type:access_token",
+            RefreshToken => "urn:ietf:params:oauth:token-type:refresh_token",
+            IdToken => "urn:ietf:params:oauth:token-type:id_token",
+            Saml1 => "urn:ietf:params:oauth:token-type:saml1",
+            Saml2 => "urn:ietf:params:oauth:token-type:saml2",
+            Jwt => "urn:ietf:params:oauth:token-type:jwt",
+            Other(ref s) => s.as_str(),
+        }
+    }
+}
+
+impl From<&str> for TokenType {
+    fn from(s: &str) -> Self {
+        match s {
+            "urn:ietf:params:oauth:token-type:access_token" => TokenType::AccessToken,
+            "urn:ietf:params:oauth:token-type:refresh_token" => TokenType::RefreshToken,
+            "urn:ietf:params:oauth:token-type:id_token" => TokenType::IdToken,
+            "urn:ietf:params:oauth:token-type:saml1" => TokenType::Saml1,
+            "urn:ietf:params:oauth:token-type:saml2" => TokenType::Saml2,
+            "urn:ietf:params:oauth:token-type:jwt" => TokenType::Jwt,
+            s => TokenType::Other(s.to_owned()),
+        }
+    }
+}
+
+impl From<String> for TokenType {
+    fn from(s: String) -> Self {
+        s.as_str().into()
+    }
+}
+
+impl From<TokenType> for String {
+    fn from(token_type: TokenType) -> Self {
+        token_type.as_str().to_owned()
+    }
+}
@@ -1,6 +1,7 @@
-use crate::Address;
+use crate::{Address, ClaimSource, LocalizedClaims};
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use url::Url;
 use validator::Validate;
 use validator_derive::Validate;
@@ -9,7 +10,7 @@ use validator_derive::Validate;
 // TODO is there a way to use claims_supported in config to simplify this struct?
 #[derive(Debug, Deserialize, Serialize, Validate, Clone, Eq, PartialEq)]
 pub struct Userinfo {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     /// Subject - Identifier for the End-User at the Issuer.
     pub sub: Option<String>,
     #[serde(default)]
@@ -75,4 +76,104 @@ pub struct Userinfo {
     #[serde(default)]
     /// Time the End-User's information was last updated. Its value is a JSON number representing the number of seconds from 1970-01-01T0:0:0Z as measured in UTC until the date/time.
     pub updated_at: Option<i64>,
+    #[serde(
+        default,
+        rename = "_claim_names",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    /// Aggregated and distributed claims, mapped to the name of their source in `claim_sources`. Resolve them with a [`ClaimsResolver`](struct.ClaimsResolver.html).
+    pub claim_names: HashMap<String, String>,
+    #[serde(
+        default,
+        rename = "_claim_sources",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    /// Sources of the aggregated and distributed claims, by name.
+    pub claim_sources: HashMap<String, ClaimSource>,
+    #[serde(flatten)]
+    /// Language-tagged variants of the string claims, such as `family_name#ja-Kana-JP`. Ask for languages with `Options::claims_locales`.
+    pub localized_claims: LocalizedClaims,
+}
+
+impl Userinfo {
+    /// The value of a string claim in the language, falling back to less specific language tags
+    /// and then to the untagged value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let userinfo: openid::Userinfo =
+    /// #     serde_json::from_str(r#"{ "name": "Yamada Taro", "name#ja-Kana-JP": "ヤマダタロウ" }"#).unwrap();
+    /// assert_eq!(Some("ヤマダタロウ"), userinfo.localized("name", "ja-Kana-JP"));
+    /// assert_eq!(Some("Yamada Taro"), userinfo.localized("name", "en"));
+    /// ```
+    pub fn localized(&self, name: &str, language: &str) -> Option<&str> {
+        self.localized_claims
+            .get(name, language)
+            .or_else(|| self.untagged(name))
+    }
+
+    fn untagged(&self, name: &str) -> Option<&str> {
+        match name {
+            "name" => self.name.as_deref(),
+            "given_name" => self.given_name.as_deref(),
+            "family_name" => self.family_name.as_deref(),
+            "middle_name" => self.middle_name.as_deref(),
+            "nickname" => self.nickname.as_deref(),
+            "preferred_username" => self.preferred_username.as_deref(),
+            "profile" => self.profile.as_ref().map(Url::as_str),
+            "picture" => self.picture.as_ref().map(Url::as_str),
+            "website" => self.website.as_ref().map(Url::as_str),
+            "email" => self.email.as_deref(),
+            "gender" => self.gender.as_deref(),
+            "zoneinfo" => self.zoneinfo.as_deref(),
+            "locale" => self.locale.as_deref(),
+            "phone_number" => self.phone_number.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::StandardClaims;
+    use serde_json::json;
+
+    #[test]
+    fn localized() {
+        let json = json!({
+            "iss": "https://example.com/",
+            "sub": "248289761001",
+            "aud": "client",
+            "exp": 1311281970,
+            "iat": 1311280970,
+            "family_name": "Yamada",
+            "family_name#ja-Kana-JP": "ヤマダ",
+            "family_name#ja-Hani-JP": "山田",
+            "website#de": "https://example.de/",
+            "email_verified": true
+        });
+        let claims: StandardClaims = serde_json::from_value(json.clone()).unwrap();
+        let userinfo = &claims.userinfo;
+        assert_eq!(
+            Some("ヤマダ"),
+            userinfo.localized("family_name", "ja-kana-jp")
+        );
+        assert_eq!(
+            Some("山田"),
+            userinfo.localized("family_name", "ja-Hani-JP-x-private")
+        );
+        assert_eq!(Some("Yamada"), userinfo.localized("family_name", "ja"));
+        assert_eq!(
+            Some("https://example.de/"),
+            userinfo.localized("website", "de-CH")
+        );
+        assert_eq!(None, userinfo.localized("given_name", "ja-Kana-JP"));
+        let serialized = serde_json::to_value(&claims).unwrap();
+        assert_eq!(
+            json["family_name#ja-Hani-JP"],
+            serialized["family_name#ja-Hani-JP"]
+        );
+        assert_eq!(json["website#de"], serialized["website#de"]);
+    }
 }
@@ -0,0 +1,45 @@
+use crate::step_up::AuthenticationRequirements;
+use biscuit::jwa::SignatureAlgorithm;
+use chrono::Duration;
+use url::Url;
+
+/// How ID tokens are validated. Set the defaults of a client on
+/// [`Client::validation`](struct.Client.html#structfield.validation) and pass other options to a
+/// single call to override them, e.g. with the nonce and max_age of your auth_url options.
+/// Derives Default, so remember to ..Default::default() after you specify what you want.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Duration;
+/// use openid::ValidationOptions;
+///
+/// let options = ValidationOptions {
+///     nonce: Some(String::from("n-0S6_WzA2Mj")),
+///     max_age: Some(Duration::minutes(5)),
+///     leeway: Duration::seconds(30),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationOptions {
+    /// Issuers to accept. Empty accepts only the issuer of the discovered config.
+    pub issuers: Vec<Url>,
+    /// Audiences trusted besides the client id. When set, tokens with any other audience are
+    /// rejected.
+    pub audiences: Vec<String>,
+    /// Signature algorithms the token header may use. Empty allows the asymmetric ones of the
+    /// provider's `id_token_signing_alg_values_supported`, or none if it advertises only others.
+    /// `none` is never allowed.
+    pub algorithms: Vec<SignatureAlgorithm>,
+    /// Clock skew tolerated for the expiry and max_age checks.
+    pub leeway: Duration,
+    /// The nonce of the auth_url options. Tokens must carry it, or no nonce if None.
+    pub nonce: Option<String>,
+    /// The max_age of the auth_url options. Tokens must carry an auth_time no older than this.
+    pub max_age: Option<Duration>,
+    /// Claims the token must have, by name.
+    pub required_claims: Vec<String>,
+    /// Required acr values and amr methods.
+    pub authentication: AuthenticationRequirements,
+}
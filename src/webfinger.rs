@@ -0,0 +1,159 @@
+/*!
+OpenID Provider issuer discovery via WebFinger.
+
+See [OpenID Connect Discovery 1.0, section 2](https://openid.net/specs/openid-connect-discovery-1_0.html#IssuerDiscovery).
+*/
+use crate::{
+    error::{Error, Webfinger},
+    http_client::{HttpClient, HttpRequest},
+};
+use http::header::ACCEPT;
+use serde::Deserialize;
+use url::Url;
+
+/// The link relation type identifying the issuer in a WebFinger response.
+pub const ISSUER_REL: &str = "http://openid.net/specs/connect/1.0/issuer";
+
+#[derive(Deserialize)]
+struct Jrd {
+    #[serde(default)]
+    links: Vec<Link>,
+}
+
+#[derive(Deserialize)]
+struct Link {
+    rel: String,
+    #[serde(default)]
+    href: Option<String>,
+}
+
+/// Find the issuer for a user supplied identifier, such as an e-mail address or a URL. The
+/// returned issuer is ready for `Client::discover`. Errors are:
+///
+/// - Webfinger::InvalidIdentifier if the identifier cannot be normalized
+/// - Error::Http if something goes wrong getting the document
+/// - Error::Json if the response is not a valid JSON Resource Descriptor
+/// - Webfinger::NoIssuer if the response has no issuer link
+/// - Error::Insecure if the issuer is not https
+pub async fn issuer(client: &dyn HttpClient, identifier: &str) -> Result<Url, Error> {
+    let request = HttpRequest::get(url(identifier)?).header(ACCEPT, "application/jrd+json")?;
+    let jrd: Jrd = client.send(request).await?.error_for_status()?.json()?;
+
+    let href = jrd
+        .links
+        .into_iter()
+        .find(|link| link.rel == ISSUER_REL)
+        .and_then(|link| link.href)
+        .ok_or(Webfinger::NoIssuer)?;
+    let issuer = Url::parse(&href).map_err(|_| Webfinger::NoIssuer)?;
+    if issuer.scheme() != "https" {
+        return Err(Error::Insecure(issuer));
+    }
+    Ok(issuer)
+}
+
+/// The WebFinger query url for a user supplied identifier.
+pub fn url(identifier: &str) -> Result<Url, Error> {
+    let (resource, host) = normalize(identifier)?;
+    let mut url = Url::parse(&format!("https://{}/.well-known/webfinger", host))
+        .map_err(|_| Webfinger::InvalidIdentifier(identifier.to_string()))?;
+    url.query_pairs_mut()
+        .append_pair("resource", &resource)
+        .append_pair("rel", ISSUER_REL);
+    Ok(url)
+}
+
+/// Normalizes the identifier to a resource and the host to query, following
+/// [spec 2.1.2](https://openid.net/specs/openid-connect-discovery-1_0.html#NormalizationSteps).
+fn normalize(identifier: &str) -> Result<(String, String), Error> {
+    let invalid = || Webfinger::InvalidIdentifier(identifier.to_string());
+    let identifier = identifier.trim();
+    if identifier.is_empty() || identifier.starts_with('#') {
+        return Err(invalid().into());
+    }
+
+    if let Some(account) = identifier.strip_prefix("acct:") {
+        let host = account.rsplit('@').next().filter(|host| !host.is_empty());
+        return match (account.contains('@'), host) {
+            (true, Some(host)) => Ok((identifier.to_string(), host.to_string())),
+            _ => Err(invalid().into()),
+        };
+    }
+
+    if !identifier.contains("://") {
+        // userinfo@host without path, query or port is an account
+        if let Some(at) = identifier.rfind('@') {
+            let host = &identifier[at + 1..];
+            if !host.is_empty() && !host.contains(&['/', '?', ':', '#'][..]) {
+                return Ok((format!("acct:{}", identifier), host.to_string()));
+            }
+        }
+    }
+
+    let mut url = if identifier.contains("://") {
+        Url::parse(identifier)
+    } else {
+        Url::parse(&format!("https://{}", identifier))
+    }
+    .map_err(|_| invalid())?;
+    url.set_fragment(None);
+
+    let host = url.host_str().ok_or_else(invalid)?;
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    Ok((String::from(url), host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize, url};
+
+    fn normalized(identifier: &str) -> (String, String) {
+        normalize(identifier).unwrap()
+    }
+
+    #[test]
+    fn normalization() {
+        assert_eq!(
+            ("acct:joe@example.com".into(), "example.com".into()),
+            normalized("joe@example.com")
+        );
+        assert_eq!(
+            ("https://example.com/joe".into(), "example.com".into()),
+            normalized("https://example.com/joe#fragment")
+        );
+        assert_eq!(
+            (
+                "https://example.com:8080/".into(),
+                "example.com:8080".into()
+            ),
+            normalized("example.com:8080")
+        );
+        assert_eq!(
+            (
+                "https://joe@example.com:8080/".into(),
+                "example.com:8080".into()
+            ),
+            normalized("joe@example.com:8080")
+        );
+        assert_eq!(
+            (
+                "acct:juliet%40capulet.example@shopping.example.com".into(),
+                "shopping.example.com".into()
+            ),
+            normalized("acct:juliet%40capulet.example@shopping.example.com")
+        );
+        assert!(normalize("").is_err());
+        assert!(normalize("acct:nobody").is_err());
+    }
+
+    #[test]
+    fn query_url() {
+        assert_eq!(
+            "https://example.com/.well-known/webfinger?resource=acct%3Ajoe%40example.com&rel=http%3A%2F%2Fopenid.net%2Fspecs%2Fconnect%2F1.0%2Fissuer",
+            url("joe@example.com").unwrap().as_str()
+        );
+    }
+}